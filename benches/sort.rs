@@ -1,5 +1,5 @@
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
-use sort_package_json::sort_package_json;
+use sort_package_json::{SortOptions, is_package_json_sorted, sort_package_json};
 
 fn bench_small_package(c: &mut Criterion) {
     let input = include_str!("../tests/fixtures/package.json");
@@ -16,6 +16,15 @@ fn bench_already_sorted(c: &mut Criterion) {
     });
 }
 
+fn bench_is_sorted(c: &mut Criterion) {
+    let input = include_str!("../tests/fixtures/package.json");
+    let sorted = sort_package_json(input).unwrap();
+    let options = SortOptions::default();
+    c.bench_function("check already sorted package.json", |b| {
+        b.iter(|| is_package_json_sorted(black_box(&sorted), &options));
+    });
+}
+
 fn bench_minimal_package(c: &mut Criterion) {
     let input = r#"{
   "version": "1.0.0",
@@ -90,6 +99,7 @@ criterion_group!(
     benches,
     bench_small_package,
     bench_already_sorted,
+    bench_is_sorted,
     bench_minimal_package,
     bench_large_package
 );
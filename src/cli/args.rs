@@ -0,0 +1,219 @@
+//! Command line arguments.
+
+use std::{
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+
+use super::{output::ColorChoice, report::Reporter};
+use sort_package_json::Indent;
+
+/// Sorts the given package.json files and all package.json files found in the given
+/// directories.
+#[derive(Parser)]
+#[command(name = "sort-package-json", args_conflicts_with_subcommands = true)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+    #[command(flatten)]
+    pub(crate) args: Args,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Sort files in place (the default)
+    Sort(Args),
+    /// List files that are not sorted and exit non-zero, without writing
+    Check(Args),
+    /// Print a unified diff of each change sorting would make, without writing
+    Diff(Args),
+    /// Only print the paths of files that are not sorted, on stdout
+    List(Args),
+    /// Run a language server on stdio that formats package.json documents
+    Lsp,
+    /// Write a .sortpackagejsonrc.json with the default field order and options to customize
+    Init {
+        /// Overwrite an existing .sortpackagejsonrc.json
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print a completion script for SHELL
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+    /// Files to sort, directories to search (defaults to the current directory), globs
+    /// matched under the current directory (e.g. `packages/*/package.json`), `!GLOB` to skip
+    /// files (e.g. `!**/fixtures/**`, quoted from the shell), or `-` to sort stdin to stdout
+    #[arg(value_name = "PATH")]
+    pub(crate) paths: Vec<String>,
+    /// List files that are not sorted and exit non-zero, without writing
+    #[arg(long)]
+    pub(crate) check: bool,
+    /// Print a unified diff of each change on stdout (with --check, of each change needed)
+    #[arg(long)]
+    pub(crate) diff: bool,
+    /// Report which files would change, without writing
+    #[arg(long)]
+    pub(crate) dry_run: bool,
+    /// Before overwriting a file, save the original next to it with SUFFIX appended
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak"
+    )]
+    pub(crate) backup: Option<String>,
+    /// Rewrite a single FILE argument instead of printing it sorted to stdout
+    #[arg(short, long)]
+    pub(crate) write: bool,
+    /// Write the sorted single FILE (or stdin) to PATH instead of stdout
+    #[arg(
+        short,
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["write", "check", "dry_run", "diff", "list_different"]
+    )]
+    pub(crate) output: Option<PathBuf>,
+    /// Show each change and ask before writing it
+    #[arg(long, conflicts_with_all = ["check", "dry_run", "list_different", "reporter", "output"])]
+    pub(crate) interactive: bool,
+    /// Make read-only files writable and rewrite them, instead of skipping them
+    #[arg(long)]
+    pub(crate) force: bool,
+    /// Only print the paths of files that would change, on stdout
+    #[arg(short, long)]
+    pub(crate) list_different: bool,
+    /// Check for CI: implies --check, --continue-on-error, and --color never, and prints the
+    /// summary as one `key=value` line
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "write", "output", "interactive", "no_error_on_unmatched_pattern"]
+    )]
+    pub(crate) ci: bool,
+    /// Keep processing the remaining files after one fails, instead of stopping
+    #[arg(long)]
+    pub(crate) continue_on_error: bool,
+    /// Skip paths that do not exist and globs that match no files, instead of failing
+    #[arg(long)]
+    pub(crate) no_error_on_unmatched_pattern: bool,
+    /// Also search directories for files whose name matches GLOB (repeatable), e.g.
+    /// `package.json.tmpl`
+    #[arg(long, value_name = "GLOB")]
+    pub(crate) include: Vec<String>,
+    /// Also process the paths listed in FILE, one per line (`-` reads them from stdin)
+    #[arg(long, value_name = "FILE", conflicts_with = "stdin_filepath")]
+    pub(crate) files_from: Option<PathBuf>,
+    /// Skip matching files and directories (repeatable), like `!GLOB`
+    #[arg(long, value_name = "GLOB")]
+    pub(crate) ignore: Vec<String>,
+    /// Don't respect .gitignore, .ignore, or other ignore files
+    #[arg(long)]
+    pub(crate) no_ignore: bool,
+    /// Don't respect .gitignore and other git ignore files
+    #[arg(long)]
+    pub(crate) no_git_ignore: bool,
+    /// Also search node_modules and other vendor directories, which are skipped by default
+    #[arg(long)]
+    pub(crate) include_node_modules: bool,
+    /// Also skip what a gitignore-style FILE lists, e.g. `.prettierignore` (repeatable)
+    #[arg(long, value_name = "FILE")]
+    pub(crate) ignore_path: Vec<PathBuf>,
+    /// Search hidden files and directories
+    #[arg(long)]
+    pub(crate) hidden: bool,
+    /// Search symlinked directories, and rewrite symlinked files wherever they point
+    #[arg(long, overrides_with = "no_follow_symlinks")]
+    pub(crate) follow_symlinks: bool,
+    /// Skip symlinked files [default: skip those pointing outside the current directory]
+    #[arg(long)]
+    pub(crate) no_follow_symlinks: bool,
+    /// Descend at most N directories below each directory searched
+    #[arg(long, value_name = "N")]
+    pub(crate) max_depth: Option<usize>,
+    /// Only sort the package.json directly in each directory
+    #[arg(long, conflicts_with = "max_depth")]
+    pub(crate) no_recursive: bool,
+    /// Only process files changed in the working tree or index, or untracked, per git
+    #[arg(long)]
+    pub(crate) changed: bool,
+    /// Only process files changed since the git REF (committed or not), or untracked
+    #[arg(long, value_name = "REF", conflicts_with = "changed")]
+    pub(crate) since: Option<String>,
+    /// Only process files staged in git, and stage the sorted result, for pre-commit hooks
+    #[arg(long, conflicts_with_all = ["changed", "since"])]
+    pub(crate) staged: bool,
+    /// Skip files that were already sorted on a previous run with the same options
+    #[arg(long)]
+    pub(crate) cache: bool,
+    /// Where --cache keeps its record of sorted files
+    #[arg(long, value_name = "PATH", requires = "cache", default_value = ".sortpackagejsoncache")]
+    pub(crate) cache_location: PathBuf,
+    /// Number of files to process at once [default: the CPU count]
+    #[arg(long, value_name = "N")]
+    pub(crate) threads: Option<NonZeroUsize>,
+    /// Only report problems, without a summary
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub(crate) quiet: bool,
+    /// Also report unchanged and skipped files, and timings
+    #[arg(short, long)]
+    pub(crate) verbose: bool,
+    /// Print wall time, per-file sort time percentiles, bytes processed, and unchanged files
+    #[arg(long)]
+    pub(crate) stats: bool,
+    /// How to report results
+    #[arg(long, value_enum, value_name = "NAME", default_value = "human")]
+    pub(crate) reporter: Reporter,
+    /// When to color status lines and diffs; `auto` colors terminals unless NO_COLOR is set
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "auto")]
+    pub(crate) color: ColorChoice,
+    /// Comma-separated top-level fields to put first, in order
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    pub(crate) order: Option<Vec<String>>,
+    /// Like --order, reading a JSON array of field names
+    #[arg(long, value_name = "PATH", conflicts_with = "order")]
+    pub(crate) order_file: Option<PathBuf>,
+    /// Indent with N spaces or a tab [default: 2]
+    #[arg(long, value_name = "N|tab", value_parser = parse_indent)]
+    pub(crate) indent: Option<Indent>,
+    /// Stay running and answer sort requests, one JSON object per line, on stdio
+    #[arg(long)]
+    pub(crate) daemon: bool,
+    /// With --daemon, listen on a unix socket at PATH instead of stdio
+    #[arg(long, value_name = "PATH", requires = "daemon")]
+    pub(crate) socket: Option<PathBuf>,
+    /// Sort stdin to stdout as if it were the file at PATH, using that file's config and
+    /// passing it through unchanged if it is ignored
+    #[arg(long, value_name = "PATH")]
+    pub(crate) stdin_filepath: Option<PathBuf>,
+}
+
+/// Parses `--indent`: a number of spaces or `tab`.
+fn parse_indent(value: &str) -> Result<Indent, String> {
+    match value {
+        "tab" => Ok(Indent::Tab),
+        _ => value
+            .parse()
+            .map(Indent::Spaces)
+            .map_err(|_| "expected a number of spaces or `tab`".to_string()),
+    }
+}
+
+/// Reads a JSON array of field names for `--order-file`.
+pub(crate) fn read_order_file(path: &Path) -> Result<Vec<String>, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    serde_json::from_str::<serde_json::Value>(&contents)
+        .map_err(|err| err.to_string())?
+        .as_array()
+        .and_then(|fields| fields.iter().map(|field| field.as_str().map(str::to_string)).collect())
+        .ok_or_else(|| "expected an array of field names".to_string())
+}
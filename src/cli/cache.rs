@@ -0,0 +1,91 @@
+//! The `--cache` record of files known to be sorted.
+
+use std::{
+    collections::{BTreeMap, HashMap, hash_map::DefaultHasher},
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use serde_json::json;
+
+use sort_package_json::{SortOptions, ruleset_version};
+
+/// The `--cache` file: a hash of the contents of each file that was sorted (or already
+/// sorted) on an earlier run. A file whose contents still hash the same is skipped. The
+/// record only holds for the ruleset and options it was made with, and is discarded when
+/// they change.
+pub(crate) struct Cache {
+    path: PathBuf,
+    /// Hash of the ruleset version and sort options.
+    key: String,
+    /// Content hash of each sorted file, by canonical path.
+    files: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl Cache {
+    /// Reads the cache at `path`. A missing or unreadable cache, or one made with other
+    /// options, starts out empty.
+    pub(crate) fn load(path: PathBuf, options: &SortOptions) -> Self {
+        // Debug output of a HashMap is unordered, so the field strategies are keyed sorted.
+        let strategies: BTreeMap<_, _> = options
+            .field_strategies
+            .iter()
+            .map(|(field, strategy)| (field, format!("{:?}", strategy)))
+            .collect();
+        let options = SortOptions { field_strategies: HashMap::new(), ..options.clone() };
+        let key = hash(&format!("{} {:?} {:?}", ruleset_version(), options, strategies));
+        let files = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+            .filter(|cache| cache["key"] == key.as_str())
+            .and_then(|cache| {
+                let files = cache["files"].as_object()?.iter();
+                Some(
+                    files
+                        .filter_map(|(file, hash)| {
+                            Some((PathBuf::from(file), hash.as_str()?.to_string()))
+                        })
+                        .collect(),
+                )
+            })
+            .unwrap_or_default();
+        Self { path, key, files: Mutex::new(files) }
+    }
+
+    /// Whether `contents` are what `path` held when it was last known to be sorted.
+    pub(crate) fn is_sorted(&self, path: &Path, contents: &str) -> bool {
+        let Ok(path) = fs::canonicalize(path) else { return false };
+        let files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files.get(&path).is_some_and(|sorted| *sorted == hash(contents))
+    }
+
+    /// Records that `path` is sorted and holds `contents`.
+    pub(crate) fn insert(&self, path: &Path, contents: &str) {
+        if let Ok(path) = fs::canonicalize(path) {
+            let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+            files.insert(path, hash(contents));
+        }
+    }
+
+    /// Writes the cache back to its file.
+    pub(crate) fn save(&self) -> io::Result<()> {
+        let files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        let files: serde_json::Map<_, _> = files
+            .iter()
+            .map(|(path, hash)| (path.to_string_lossy().into_owned(), json!(hash)))
+            .collect();
+        let cache = json!({ "key": self.key, "files": files });
+        fs::write(&self.path, cache.to_string())
+    }
+}
+
+/// A hex content hash for [`Cache`]. It only needs to be stable between runs of the same
+/// build; a different hash after an upgrade just misses the cache.
+fn hash(contents: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
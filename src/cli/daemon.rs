@@ -0,0 +1,90 @@
+//! `--daemon`: sorting on request over stdio or a unix socket.
+
+use std::{
+    env, fs,
+    io::{self, BufRead, Write},
+    path::Path,
+    thread,
+};
+
+use serde_json::json;
+use sort_package_json::sort_package_json_with_outcome;
+
+use super::{options::OptionsCache, process::FileError};
+
+/// Answers sort requests for `--daemon`, one JSON object per line each way.
+///
+/// A request is `{ "path": "...", "contents": "..." }`, where `contents` defaults to the
+/// file at `path` and the config nearest `path` applies. The response is
+/// `{ "output": "...", "changed": true }` or `{ "error": "..." }`. Files are never written.
+pub(crate) struct Daemon {
+    pub(crate) options: OptionsCache,
+}
+
+impl Daemon {
+    /// Answers each request line from `reader` on `writer`, until `reader` ends.
+    pub(crate) fn serve<R: BufRead, W: Write>(&self, reader: R, mut writer: W) -> io::Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match self.handle(&line) {
+                Ok((output, changed)) => json!({ "output": output, "changed": changed }),
+                Err(err) => json!({ "error": err }),
+            };
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Serves each connection to a unix socket at `path` on its own thread, forever.
+    #[cfg(unix)]
+    pub(crate) fn serve_socket(&self, path: &Path) -> io::Result<()> {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::UnixListener;
+
+        // Replace a socket left behind by an earlier daemon, but never another kind of file.
+        if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let stream = stream?;
+                scope.spawn(move || {
+                    // A client hanging up only ends its own connection.
+                    let _ = self.serve(io::BufReader::new(&stream), &stream);
+                });
+            }
+            Ok(())
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn serve_socket(&self, _path: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "unix sockets are not supported here"))
+    }
+
+    /// Sorts the document a request names, returning the output and whether it changed.
+    fn handle(&self, request: &str) -> Result<(String, bool), String> {
+        let request: serde_json::Value =
+            serde_json::from_str(request).map_err(|err| format!("Invalid request: {}", err))?;
+        let path = request
+            .get("path")
+            .and_then(serde_json::Value::as_str)
+            .ok_or("Invalid request: missing `path`")?;
+        let path = env::current_dir().map_err(|err| err.to_string())?.join(path);
+        let contents = match request.get("contents") {
+            Some(contents) => {
+                contents.as_str().ok_or("Invalid request: `contents` must be a string")?.to_string()
+            }
+            None => fs::read_to_string(&path).map_err(|err| FileError::read(err).message)?,
+        };
+        let options = self.options.get(&path)?;
+        let outcome = sort_package_json_with_outcome(&contents, &options)
+            .map_err(|err| FileError::sort(err).message)?;
+        Ok((outcome.output, outcome.changed))
+    }
+}
@@ -0,0 +1,62 @@
+//! The `init` command.
+
+use serde_json::json;
+
+use sort_package_json::{
+    ExportsMode, Indent, LineEnding, PrivateFieldPlacement, SortOptions, TrailingNewline,
+};
+
+/// The config `init` writes: every configurable option of [`SortOptions::default`]. The field
+/// order is written as `"canonical"` rather than listed, so later releases' field order
+/// still applies.
+pub(crate) fn default_config() -> serde_json::Value {
+    let options = SortOptions::default();
+    json!({
+        "fieldOrder": "canonical",
+        "exclude": [],
+        "ignore": [],
+        "pretty": options.pretty,
+        "lineWidth": options.line_width,
+        "indent": match options.indent {
+            Indent::Spaces(width) => json!(width),
+            Indent::Tab => json!("tab"),
+        },
+        "groupSections": options.group_sections,
+        "lineEnding": match options.line_ending {
+            LineEnding::Lf => "lf",
+            LineEnding::CrLf => "crlf",
+        },
+        "trailingNewline": match options.trailing_newline {
+            TrailingNewline::PrettyOnly => "prettyOnly",
+            TrailingNewline::Always => "always",
+            TrailingNewline::Never => "never",
+            TrailingNewline::Preserve => "preserve",
+        },
+        "topLevelOnly": options.top_level_only,
+        "rejectDuplicateKeys": options.reject_duplicate_keys,
+        "lenient": options.lenient,
+        "normalize": options.normalize,
+        "sortScripts": options.sort_scripts,
+        "sortDependencies": options.sort_dependencies,
+        "caseInsensitiveDependencies": options.case_insensitive_dependencies,
+        "dependencyOrder": options.dependency_order,
+        "sortFiles": options.sort_files,
+        "exportsMode": match options.exports_mode {
+            ExportsMode::Preserve => "preserve",
+            ExportsMode::GroupPaths => "groupPaths",
+            ExportsMode::Off => "off",
+        },
+        "dedupeArrays": options.dedupe_arrays,
+        "sortUnknownFields": options.sort_unknown_fields,
+        "removeEmptyFields": options.remove_empty_fields,
+        "keepEmptyFields": options.keep_empty_fields,
+        "privateFields": match options.private_fields {
+            PrivateFieldPlacement::First => "first",
+            PrivateFieldPlacement::Last => "last",
+            PrivateFieldPlacement::Preserve => "preserve",
+            PrivateFieldPlacement::Merge => "merge",
+        },
+        "naturalSort": options.natural_sort,
+        "unicodeCollation": options.unicode_collation,
+    })
+}
@@ -0,0 +1,203 @@
+//! The `lsp` command: a language server formatting package.json documents.
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use serde_json::json;
+use sort_package_json::sort_package_json_edits;
+
+use super::{MANIFEST_NAME, options::OptionsCache};
+
+/// A minimal language server for the `lsp` command: it keeps open documents in sync and
+/// answers `textDocument/formatting` and `textDocument/rangeFormatting` for files named
+/// `package.json`. Sorting moves fields across the whole document, so a range request
+/// formats all of it.
+pub(crate) struct LanguageServer {
+    options: OptionsCache,
+    /// Text of each open document, by URI.
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl LanguageServer {
+    pub(crate) fn new(options: OptionsCache) -> Self {
+        Self { options, documents: HashMap::new(), shutdown: false }
+    }
+
+    /// Handles messages until the client sends `exit` or closes stdin, returning whether it
+    /// asked to shut down first.
+    pub(crate) fn run<R: BufRead, W: Write>(
+        &mut self,
+        mut reader: R,
+        mut writer: W,
+    ) -> io::Result<bool> {
+        while let Some(message) = read_message(&mut reader)? {
+            let method = message.get("method").and_then(serde_json::Value::as_str);
+            if method == Some("exit") {
+                break;
+            }
+            let params = message.get("params").unwrap_or(&serde_json::Value::Null);
+            let result = method.map(|method| self.handle(method, params));
+            // Notifications have no `id` and get no response.
+            let Some(id) = message.get("id") else { continue };
+            let response = match result {
+                Some(Some(result)) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                _ => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": "method not found" },
+                }),
+            };
+            let body = response.to_string();
+            write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+            writer.flush()?;
+        }
+        Ok(self.shutdown)
+    }
+
+    /// Handles a request or notification, returning the result, or `None` for an unknown
+    /// method.
+    fn handle(&mut self, method: &str, params: &serde_json::Value) -> Option<serde_json::Value> {
+        let uri = params.pointer("/textDocument/uri").and_then(serde_json::Value::as_str);
+        match method {
+            "initialize" => Some(json!({
+                "capabilities": {
+                    // Full document sync.
+                    "textDocumentSync": 1,
+                    "documentFormattingProvider": true,
+                    "documentRangeFormattingProvider": true,
+                },
+                "serverInfo": { "name": "sort-package-json", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(serde_json::Value::Null)
+            }
+            "textDocument/didOpen" => {
+                let text = params.pointer("/textDocument/text").and_then(serde_json::Value::as_str);
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                Some(serde_json::Value::Null)
+            }
+            "textDocument/didChange" => {
+                // With full sync, the last change holds the whole text.
+                let text = params
+                    .get("contentChanges")
+                    .and_then(serde_json::Value::as_array)
+                    .and_then(|changes| changes.last()?.get("text")?.as_str());
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                Some(serde_json::Value::Null)
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = uri {
+                    self.documents.remove(uri);
+                }
+                Some(serde_json::Value::Null)
+            }
+            "textDocument/formatting" | "textDocument/rangeFormatting" => {
+                Some(uri.and_then(|uri| self.format(uri)).unwrap_or(serde_json::Value::Null))
+            }
+            // Other notifications, such as `initialized`, need no handling.
+            _ if method.starts_with("$/") || method == "initialized" => {
+                Some(serde_json::Value::Null)
+            }
+            _ => None,
+        }
+    }
+
+    /// The edits sorting the open document at `uri`, touching only the lines that move, or
+    /// none if it is sorted. `None` if the document is not a manifest or cannot be sorted.
+    fn format(&self, uri: &str) -> Option<serde_json::Value> {
+        let path = uri_to_path(uri)?;
+        if path.file_name()? != MANIFEST_NAME {
+            return None;
+        }
+        let text = self.documents.get(uri)?;
+        let options = self.options.get(&path).ok()?;
+        let edits = sort_package_json_edits(text, &options).ok()?;
+        Some(
+            edits
+                .iter()
+                .map(|edit| {
+                    json!({
+                        "range": {
+                            "start": position(text, edit.range.start),
+                            "end": position(text, edit.range.end),
+                        },
+                        "newText": edit.new_text,
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The LSP position of byte `offset` in `text`. Characters count UTF-16 code units.
+fn position(text: &str, offset: usize) -> serde_json::Value {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+/// Reads one `Content-Length`-framed message, or `None` at the end of input.
+pub(crate) fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<serde_json::Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map(Some).map_err(io::Error::from)
+}
+
+/// The path of a `file:` URI, with percent-escapes decoded.
+pub(crate) fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let decoded = String::from_utf8(decoded).ok()?;
+    // `file:///C:/dir` on Windows.
+    let decoded = match decoded.as_bytes() {
+        [b'/', _, b':', ..] if cfg!(windows) => &decoded[1..],
+        _ => &decoded,
+    };
+    Some(PathBuf::from(decoded))
+}
@@ -0,0 +1,938 @@
+//! The `sort-package-json` command line interface.
+
+mod args;
+mod cache;
+mod daemon;
+mod init;
+mod lsp;
+mod options;
+mod output;
+mod process;
+mod report;
+mod walk;
+mod write;
+
+use std::{
+    collections::HashSet,
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+use globset::GlobSet;
+use sort_package_json::{Config, find_config_file};
+
+use self::{
+    args::{Args, Cli, Command, read_order_file},
+    cache::Cache,
+    daemon::Daemon,
+    init::default_config,
+    lsp::LanguageServer,
+    options::{OptionsCache, Overrides},
+    output::{ColorChoice, Colors, GREEN, RED, Stats, Verbosity, YELLOW, print_diff},
+    process::{FileError, Processed, Settings, print_sorted, process_files, process_interactively},
+    report::{Entry, Reporter, checkstyle_report, json_report, junit_report, sarif_report},
+    walk::{VENDOR_DIRS, WalkOptions, git_files, glob_set, is_ignored, relative, walk},
+};
+
+/// The file name searched for in directories.
+const MANIFEST_NAME: &str = "package.json";
+
+/// Commented manifests also searched for in directories, see [`process::sort_manifest`].
+#[cfg(feature = "json5")]
+const COMMENTED_MANIFEST_NAMES: &[&str] = &["package.json5", "package.jsonc"];
+#[cfg(not(feature = "json5"))]
+const COMMENTED_MANIFEST_NAMES: &[&str] = &[];
+
+/// The exit status. When several apply, the highest wins, so scripts can tell unsorted
+/// files from broken ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Exit {
+    /// Everything is (now) sorted.
+    Success,
+    /// Files need sorting (`check`, `list`).
+    Unsorted,
+    /// A file is not a valid package.json.
+    Parse,
+    /// A file could not be read or written.
+    Io,
+    /// Invalid arguments or config.
+    Usage,
+    /// The language server got `exit` (or end of input) without a `shutdown` request, which
+    /// the protocol makes an error.
+    NoShutdown,
+}
+
+impl Exit {
+    /// The process exit code.
+    pub(crate) fn code(self) -> i32 {
+        match self {
+            Self::Success => 0,
+            Self::Unsorted => 1,
+            Self::Parse => 2,
+            Self::Io => 3,
+            Self::Usage => 4,
+            Self::NoShutdown => 5,
+        }
+    }
+}
+
+/// Runs the command line `args` (starting with the program name), returning the exit status.
+pub(crate) fn run<I, T>(args: I) -> Exit
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let start = Instant::now();
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            // `--help` and `--version` print to stdout and succeed.
+            return if err.use_stderr() { Exit::Usage } else { Exit::Success };
+        }
+    };
+    let args = match cli.command {
+        Some(Command::Lsp) => return serve_lsp(),
+        Some(Command::Init { force }) => return init(force),
+        Some(Command::Completions { shell }) => return completions(shell),
+        None => cli.args,
+        Some(Command::Sort(args)) => args,
+        Some(Command::Check(args)) => Args { check: true, ..args },
+        Some(Command::Diff(args)) => Args { dry_run: true, diff: true, ..args },
+        Some(Command::List(args)) => Args { list_different: true, ..args },
+    };
+    sort(args, start)
+}
+
+#[allow(clippy::print_stderr)]
+fn serve_lsp() -> Exit {
+    let overrides = Overrides { field_order: None, indent: None };
+    let mut server = LanguageServer::new(OptionsCache::new(overrides));
+    match server.run(io::stdin().lock(), io::stdout().lock()) {
+        Ok(true) => Exit::Success,
+        Ok(false) => Exit::NoShutdown,
+        Err(err) => {
+            eprintln!("Error: Language server failed: {err}");
+            Exit::Io
+        }
+    }
+}
+
+#[allow(clippy::print_stderr)]
+fn init(force: bool) -> Exit {
+    let path = Path::new(".sortpackagejsonrc.json");
+    if path.exists() && !force {
+        eprintln!("Error: {} already exists (use --force to replace it)", path.display());
+        return Exit::Usage;
+    }
+    if let Err(err) = fs::write(path, format!("{:#}\n", default_config())) {
+        eprintln!("Error: Failed to write {}: {}", path.display(), err);
+        return Exit::Io;
+    }
+    eprintln!("Created {}", path.display());
+    Exit::Success
+}
+
+fn completions(shell: Shell) -> Exit {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    // Generated in memory, as writing to a closed pipe would panic.
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, name, &mut script);
+    let _ = io::stdout().write_all(&script);
+    Exit::Success
+}
+
+/// Sorts, checks or lists files as `args` ask.
+#[allow(clippy::print_stderr)]
+fn sort(args: Args, start: Instant) -> Exit {
+    let args = if args.ci {
+        Args { check: true, continue_on_error: true, color: ColorChoice::Never, ..args }
+    } else {
+        args
+    };
+    let output = Output::new(&args);
+    let cwd = env::current_dir().ok();
+
+    let mut targets = match Targets::new(&args) {
+        Ok(targets) => targets,
+        Err(exit) => return exit,
+    };
+    let mut walk_options = match walk_options(&args) {
+        Ok(walk_options) => walk_options,
+        Err(exit) => return exit,
+    };
+    let (config, overrides) = match load_config(&args, cwd.as_deref(), output.verbosity) {
+        Ok(loaded) => loaded,
+        Err(exit) => return exit,
+    };
+    targets.excludes.extend(config.ignore);
+    let mut settings = Settings {
+        check: args.check,
+        diff: args.diff,
+        dry_run: args.dry_run,
+        backup: args.backup.clone(),
+        force: args.force,
+        options: config.options,
+        ..Settings::default()
+    };
+    if output.reporter == Reporter::List {
+        // Listing never writes, and fails when something is listed, like `--check`.
+        settings.check = true;
+    }
+
+    if args.daemon {
+        return serve_daemon(overrides, args.socket.as_deref());
+    }
+
+    let stdin =
+        args.stdin_filepath.is_some() || targets.paths.iter().any(|path| path == Path::new("-"));
+    if stdin
+        && (targets.paths.iter().any(|path| path != Path::new("-")) || !targets.includes.is_empty())
+    {
+        eprintln!("Error: `-` and --stdin-filepath cannot be combined with other paths");
+        return Exit::Usage;
+    }
+    // An empty --files-from list means nothing changed, not the current directory.
+    if targets.paths.is_empty() && targets.includes.is_empty() && args.files_from.is_none() {
+        targets.paths.push(PathBuf::from("."));
+    }
+    walk_options.log_skipped = output.verbosity == Verbosity::Verbose;
+    let globs = match Globs::new(&targets, &args.include) {
+        Ok(globs) => globs,
+        Err(exit) => return exit,
+    };
+
+    // Like prettier, a single file is printed rather than rewritten unless --write is given.
+    let print = !args.write
+        && !args.interactive
+        && !settings.check
+        && !settings.dry_run
+        && output.reporter == Reporter::Human
+        && !args.staged
+        && !args.changed
+        && args.since.is_none()
+        && targets.includes.is_empty()
+        && args.files_from.is_none()
+        && matches!(targets.paths.as_slice(), [path] if path.is_file());
+    if args.output.is_some() && !stdin && !print {
+        eprintln!("Error: --output takes a single file or `-`");
+        return Exit::Usage;
+    }
+    if print {
+        let path = targets.paths.first().map(PathBuf::as_path);
+        return sort_single(&args, path, true, false, &settings, output);
+    }
+    if stdin {
+        let ignored =
+            args.stdin_filepath.as_deref().zip(cwd.as_deref()).is_some_and(|(path, cwd)| {
+                is_excluded(&cwd.join(path), cwd, &globs.excludes, &walk_options)
+            });
+        if ignored && output.verbosity == Verbosity::Verbose {
+            let path = args.stdin_filepath.as_deref().unwrap_or(Path::new("-"));
+            eprintln!("- Skipped: {} (excluded)", path.display());
+        }
+        return sort_single(
+            &args,
+            args.stdin_filepath.as_deref(),
+            false,
+            ignored,
+            &settings,
+            output,
+        );
+    }
+
+    let mut files =
+        match select_files(&args, targets, &globs, &walk_options, cwd.as_deref(), output.verbosity)
+        {
+            Ok(files) => files,
+            Err(exit) => return exit,
+        };
+    let mut exit = Exit::Success;
+    if args.staged {
+        match filter_staged(&mut files, output.verbosity) {
+            Ok(true) => exit = Exit::Usage,
+            Ok(false) => {}
+            Err(exit) => return exit,
+        }
+    }
+
+    if args.cache {
+        settings.cache = Some(Cache::load(args.cache_location.clone(), &settings.options));
+    }
+    exit.max(process_and_report(&args, &files, &settings, output, start))
+}
+
+/// How results are shown.
+#[derive(Clone, Copy)]
+struct Output {
+    reporter: Reporter,
+    verbosity: Verbosity,
+    colors: Colors,
+}
+
+impl Output {
+    fn new(args: &Args) -> Self {
+        let reporter = if args.list_different { Reporter::List } else { args.reporter };
+        let verbosity = if args.quiet || reporter != Reporter::Human {
+            // Keep stderr free of per-file lines; reports are on stdout.
+            Verbosity::Quiet
+        } else if args.verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        };
+        Self { reporter, verbosity, colors: Colors::new(args.color) }
+    }
+}
+
+/// The paths and patterns to sort, from the command line and `--files-from`.
+struct Targets {
+    /// Files and directories given by name.
+    paths: Vec<PathBuf>,
+    /// Globs matched against files under the current directory.
+    includes: Vec<String>,
+    /// Globs (`!pattern`, `--ignore` and the config's `ignore`) for paths to skip.
+    excludes: Vec<String>,
+}
+
+impl Targets {
+    #[allow(clippy::print_stderr)]
+    fn new(args: &Args) -> Result<Self, Exit> {
+        let mut targets =
+            Self { paths: Vec::new(), includes: Vec::new(), excludes: args.ignore.clone() };
+        for arg in &args.paths {
+            if let Some(pattern) = arg.strip_prefix('!') {
+                targets.excludes.push(pattern.to_string());
+            } else if arg.contains(['*', '?', '[', '{']) {
+                targets.includes.push(arg.clone());
+            } else {
+                targets.paths.push(PathBuf::from(arg));
+            }
+        }
+        if let Some(list) = &args.files_from {
+            let contents = if list == Path::new("-") {
+                io::read_to_string(io::stdin())
+            } else {
+                fs::read_to_string(list)
+            };
+            match contents {
+                Ok(contents) => targets.paths.extend(
+                    contents
+                        .lines()
+                        .map(|line| line.trim_end_matches('\r'))
+                        .filter(|line| !line.is_empty())
+                        .map(PathBuf::from),
+                ),
+                Err(err) => {
+                    eprintln!("Error: Failed to read {}: {}", list.display(), err);
+                    return Err(Exit::Io);
+                }
+            }
+        }
+        Ok(targets)
+    }
+}
+
+/// The compiled patterns of [`Targets`] and `--include`.
+struct Globs {
+    includes: GlobSet,
+    excludes: GlobSet,
+    /// File names searched for in directories besides `package.json`.
+    names: GlobSet,
+}
+
+impl Globs {
+    #[allow(clippy::print_stderr)]
+    fn new(targets: &Targets, names: &[String]) -> Result<Self, Exit> {
+        let compile = |patterns: &[String]| {
+            glob_set(&patterns.iter().map(String::as_str).collect::<Vec<_>>())
+        };
+        match (compile(&targets.includes), compile(&targets.excludes), compile(names)) {
+            (Ok(includes), Ok(excludes), Ok(names)) => Ok(Self { includes, excludes, names }),
+            (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+                eprintln!("Error: Invalid glob: {}", err);
+                Err(Exit::Usage)
+            }
+        }
+    }
+}
+
+#[allow(clippy::print_stderr)]
+fn walk_options(args: &Args) -> Result<WalkOptions, Exit> {
+    let ignore_paths = args
+        .ignore_path
+        .iter()
+        .map(|path| fs::canonicalize(path).map_err(|err| format!("{}: {}", path.display(), err)));
+    let ignore_paths = match ignore_paths.collect() {
+        Ok(ignore_paths) => ignore_paths,
+        Err(err) => {
+            eprintln!("Error: Invalid --ignore-path {}", err);
+            return Err(Exit::Usage);
+        }
+    };
+    Ok(WalkOptions {
+        no_ignore: args.no_ignore,
+        no_git_ignore: args.no_git_ignore,
+        include_node_modules: args.include_node_modules,
+        ignore_paths,
+        hidden: args.hidden,
+        follow_symlinks: args.follow_symlinks,
+        max_depth: if args.no_recursive { Some(0) } else { args.max_depth },
+        log_skipped: false,
+    })
+}
+
+/// Loads the nearest config file, if any, and applies the command line settings over it.
+/// With `--stdin-filepath` the config nearest that file is used.
+#[allow(clippy::print_stderr)]
+fn load_config(
+    args: &Args,
+    cwd: Option<&Path>,
+    verbosity: Verbosity,
+) -> Result<(Config, Overrides), Exit> {
+    let config_dir = match (cwd, &args.stdin_filepath) {
+        (Some(cwd), Some(path)) => cwd.join(path).parent().map(Path::to_path_buf),
+        (cwd, _) => cwd.map(Path::to_path_buf),
+    };
+    let mut config = match config_dir.and_then(find_config_file) {
+        Some(path) => match Config::from_file(&path) {
+            Ok(config) => {
+                if verbosity == Verbosity::Verbose {
+                    eprintln!("Using config: {}", path.display());
+                }
+                config
+            }
+            Err(err) => {
+                eprintln!("Error: {}: {}", path.display(), err);
+                return Err(Exit::Usage);
+            }
+        },
+        None => Config::default(),
+    };
+
+    let field_order = match (&args.order, &args.order_file) {
+        (Some(order), _) => Some(
+            order
+                .iter()
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ),
+        (None, Some(order_file)) => match read_order_file(order_file) {
+            Ok(fields) => Some(fields),
+            Err(err) => {
+                eprintln!("Error: Invalid order file {}: {}", order_file.display(), err);
+                return Err(Exit::Usage);
+            }
+        },
+        (None, None) => None,
+    };
+    let overrides = Overrides { field_order, indent: args.indent };
+    overrides.apply(&mut config.options);
+    Ok((config, overrides))
+}
+
+/// Hands off to the daemon, which takes requests on `socket` or stdin until closed.
+#[allow(clippy::print_stderr)]
+fn serve_daemon(overrides: Overrides, socket: Option<&Path>) -> Exit {
+    let daemon = Daemon { options: OptionsCache::new(overrides) };
+    let served = match socket {
+        Some(socket) => daemon.serve_socket(socket),
+        None => daemon.serve(io::stdin().lock(), io::stdout()),
+    };
+    if let Err(err) = served {
+        eprintln!("Error: Daemon failed: {}", err);
+        return Exit::Io;
+    }
+    Exit::Success
+}
+
+/// Whether the stdin path is skipped: it, or a directory containing it, would be skipped
+/// when searching the current directory.
+fn is_excluded(path: &Path, cwd: &Path, excludes: &GlobSet, walk_options: &WalkOptions) -> bool {
+    let under_cwd = path.strip_prefix(cwd).unwrap_or(path);
+    let vendored = under_cwd.parent().is_some_and(|dir| {
+        dir.components().any(|dir| VENDOR_DIRS.iter().any(|name| dir.as_os_str() == *name))
+    });
+    under_cwd.ancestors().any(|p| !p.as_os_str().is_empty() && excludes.is_match(p))
+        || (vendored && !walk_options.include_node_modules)
+        || is_ignored(path, walk_options)
+}
+
+/// Sorts the file at `path`, or stdin unless `read_path`, to stdout or `--output`. For
+/// stdin `path` is `--stdin-filepath`, which labels errors.
+#[allow(clippy::print_stderr)]
+fn sort_single(
+    args: &Args,
+    path: Option<&Path>,
+    read_path: bool,
+    ignored: bool,
+    settings: &Settings,
+    output: Output,
+) -> Exit {
+    let contents = match path.filter(|_| read_path) {
+        Some(path) => fs::read_to_string(path),
+        None => io::read_to_string(io::stdin()),
+    };
+    let mut sorted = Vec::new();
+    let result = contents
+        .map_err(FileError::read)
+        .and_then(|contents| {
+            print_sorted(&contents, &mut sorted, settings, path, ignored, output.colors)
+        })
+        .and_then(|changed| {
+            match &args.output {
+                Some(output) => fs::write(output, &sorted),
+                None => io::stdout().write_all(&sorted),
+            }
+            .map_err(FileError::write)?;
+            Ok(changed)
+        });
+    match result {
+        Ok(changed) if settings.check && changed => Exit::Unsorted,
+        Ok(_) => Exit::Success,
+        Err(err) => {
+            let name = path.map_or_else(|| "stdin".to_string(), |p| p.display().to_string());
+            eprintln!("{} {}: {}", output.colors.stderr(RED, "✗ Error processing"), name, err);
+            err.code
+        }
+    }
+}
+
+/// Finds the files to process: files are sorted as given, directories are searched for
+/// package.json files and any names given with `--include`, and patterns are matched
+/// under the current directory. Then excluded files, symlinks leaving the current
+/// directory, duplicates and, with `--changed` or `--since`, unchanged files are dropped.
+#[allow(clippy::print_stderr)]
+fn select_files(
+    args: &Args,
+    targets: Targets,
+    globs: &Globs,
+    walk_options: &WalkOptions,
+    cwd: Option<&Path>,
+    verbosity: Verbosity,
+) -> Result<Vec<PathBuf>, Exit> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in targets.paths {
+        if path.is_file() {
+            files.push(path);
+        } else if path.is_dir() {
+            files.extend(
+                walk(&path, &globs.excludes, walk_options)
+                    .filter_map(Result::ok)
+                    .filter(|e| e.file_type().is_some_and(|ty| !ty.is_dir()))
+                    .filter(|e| {
+                        e.file_name() == MANIFEST_NAME
+                            || COMMENTED_MANIFEST_NAMES.iter().any(|name| e.file_name() == *name)
+                            || globs.names.is_match(e.file_name())
+                    })
+                    .map(|e| relative(e.path()).to_path_buf()),
+            );
+        } else if args.no_error_on_unmatched_pattern {
+            if verbosity == Verbosity::Verbose {
+                eprintln!("- Skipped: {} (does not exist)", path.display());
+            }
+        } else {
+            eprintln!("Error: Path does not exist: {}", path.display());
+            return Err(Exit::Io);
+        }
+    }
+    if !targets.includes.is_empty() {
+        let mut matched = vec![false; targets.includes.len()];
+        files.extend(
+            walk(Path::new("."), &globs.excludes, walk_options)
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_some_and(|ty| !ty.is_dir()))
+                .map(|e| relative(e.path()).to_path_buf())
+                .filter(|path| {
+                    let hits = globs.includes.matches(path);
+                    for &i in &hits {
+                        matched[i] = true;
+                    }
+                    !hits.is_empty()
+                }),
+        );
+        for (pattern, _) in targets.includes.iter().zip(matched).filter(|(_, matched)| !matched) {
+            if args.no_error_on_unmatched_pattern {
+                if verbosity == Verbosity::Verbose {
+                    eprintln!("- Skipped: {} (no files match)", pattern);
+                }
+            } else {
+                eprintln!("Error: No files match pattern: {}", pattern);
+                return Err(Exit::Usage);
+            }
+        }
+    }
+    files.retain(|path| {
+        let skip = globs.excludes.is_match(relative(path));
+        if skip && verbosity == Verbosity::Verbose {
+            eprintln!("- Skipped: {} (excluded)", path.display());
+        }
+        !skip
+    });
+    if !args.follow_symlinks {
+        // By default a symlinked file is only rewritten if it points into the current
+        // directory, so linked packages elsewhere are left alone.
+        let root = cwd.and_then(|cwd| fs::canonicalize(cwd).ok());
+        files.retain(|path| {
+            let is_symlink = fs::symlink_metadata(path).is_ok_and(|meta| meta.is_symlink());
+            let skip = is_symlink
+                && (args.no_follow_symlinks
+                    || !fs::canonicalize(path).is_ok_and(|target| {
+                        root.as_ref().is_some_and(|root| target.starts_with(root))
+                    }));
+            if skip && verbosity == Verbosity::Verbose {
+                eprintln!("- Skipped: {} (symlink)", path.display());
+            }
+            !skip
+        });
+    }
+    // Each file is processed once, however many links lead to it.
+    let mut seen = HashSet::new();
+    files.retain(|path| fs::canonicalize(path).map_or(true, |path| seen.insert(path)));
+    if let Some(base) = args.since.as_deref().or(args.changed.then_some("HEAD")) {
+        let commands: [&[&str]; 2] = [
+            &["diff", "--name-only", "--relative", "--diff-filter=d", "-z", base, "--"],
+            &["ls-files", "--others", "--exclude-standard", "-z"],
+        ];
+        let changed = match git_files(&commands) {
+            Ok(changed) => changed,
+            Err(err) => {
+                eprintln!("Error: Failed to list changed files: {}", err);
+                return Err(Exit::Usage);
+            }
+        };
+        files.retain(|path| {
+            let keep = fs::canonicalize(path).is_ok_and(|path| changed.contains(&path));
+            if !keep && verbosity == Verbosity::Verbose {
+                eprintln!("- Skipped: {} (unchanged since {})", path.display(), base);
+            }
+            keep
+        });
+    }
+    Ok(files)
+}
+
+/// Keeps only staged files, for `--staged`. Returns whether a file was dropped for also
+/// having unstaged changes, which staging the sorted file would stage too.
+#[allow(clippy::print_stderr)]
+fn filter_staged(files: &mut Vec<PathBuf>, verbosity: Verbosity) -> Result<bool, Exit> {
+    let staged =
+        git_files(&[&["diff", "--cached", "--name-only", "--relative", "--diff-filter=d", "-z"]]);
+    let unstaged = git_files(&[&["diff", "--name-only", "--relative", "-z"]]);
+    let (staged, unstaged) = match (staged, unstaged) {
+        (Ok(staged), Ok(unstaged)) => (staged, unstaged),
+        (Err(err), _) | (_, Err(err)) => {
+            eprintln!("Error: Failed to list staged files: {}", err);
+            return Err(Exit::Usage);
+        }
+    };
+    let mut conflicted = false;
+    files.retain(|path| {
+        let canonical = fs::canonicalize(path).ok();
+        if !canonical.as_ref().is_some_and(|path| staged.contains(path)) {
+            if verbosity == Verbosity::Verbose {
+                eprintln!("- Skipped: {} (not staged)", path.display());
+            }
+            return false;
+        }
+        if canonical.is_some_and(|path| unstaged.contains(&path)) {
+            eprintln!("Error: {} has unstaged changes; stage or stash them first", path.display());
+            conflicted = true;
+            return false;
+        }
+        true
+    });
+    Ok(conflicted)
+}
+
+/// How many files ended up how, for the summary.
+#[derive(Default)]
+struct Tally {
+    sorted: usize,
+    declined: usize,
+    read_only: usize,
+    unsorted: usize,
+    errors: usize,
+}
+
+/// Processes `files`, reports each result and the summary, saves the cache and restages
+/// files sorted with `--staged`.
+#[allow(clippy::print_stderr)]
+fn process_and_report(
+    args: &Args,
+    files: &[PathBuf],
+    settings: &Settings,
+    output: Output,
+    start: Instant,
+) -> Exit {
+    let threads = args
+        .threads
+        .map_or_else(|| thread::available_parallelism().map_or(1, usize::from), usize::from);
+    // Per-file lines are printed once every file is processed, so a progress line in the
+    // meantime never interleaves with them.
+    let progress = output.verbosity != Verbosity::Quiet && io::stderr().is_terminal();
+    let results = if args.interactive {
+        process_interactively(files, settings, output.colors)
+    } else {
+        process_files(files, settings, threads, progress, !args.continue_on_error)
+    };
+    let not_processed = files.len() - results.len();
+
+    let mut exit = Exit::Success;
+    let mut tally = Tally::default();
+    let mut restage = Vec::new();
+    let mut entries = Vec::new();
+    let mut stats = Stats::default();
+    for (file_path, (result, elapsed)) in results {
+        stats.times.push(elapsed);
+        if args.staged && !settings.check && !settings.dry_run {
+            if let Ok(Processed { changed: true, .. }) = &result {
+                restage.push(file_path);
+            }
+        }
+        if let Ok(processed) = &result {
+            stats.bytes += processed.bytes;
+            stats.unchanged += usize::from(!processed.changed);
+        }
+        if let Err(err) = &result {
+            tally.errors += 1;
+            exit = exit.max(err.code);
+        }
+        match output.reporter {
+            reporter if reporter.is_document() => {
+                entries.push(report_entry(file_path, result, settings, &mut tally));
+            }
+            Reporter::List => list_result(file_path, result, output, &mut tally),
+            _ => print_result(file_path, result, elapsed, settings, output, &mut tally),
+        }
+    }
+
+    let report = match output.reporter {
+        Reporter::Json => Some(json_report(&entries)),
+        Reporter::Checkstyle => Some(checkstyle_report(&entries)),
+        Reporter::Junit => Some(junit_report(&entries)),
+        Reporter::Sarif => Some(sarif_report(&entries)),
+        Reporter::Human | Reporter::List => None,
+    };
+    if let Some(report) = report {
+        if let Err(err) = writeln!(io::stdout(), "{}", report) {
+            eprintln!("Error: Failed to write report: {}", err);
+            return Exit::Io;
+        }
+    } else if args.ci {
+        eprintln!(
+            "\nsummary: found={} unsorted={} errors={}",
+            files.len(),
+            tally.unsorted,
+            tally.errors
+        );
+    } else if output.verbosity != Verbosity::Quiet {
+        eprintln!("\nSummary:");
+        eprintln!("  Found: {}", files.len());
+        if settings.check {
+            eprintln!("  Not sorted: {}", tally.unsorted);
+        } else if settings.dry_run {
+            eprintln!("  Would sort: {}", tally.unsorted);
+        } else {
+            eprintln!("  Sorted: {}", tally.sorted);
+        }
+        if tally.declined > 0 {
+            eprintln!("  Declined: {}", tally.declined);
+        }
+        if tally.read_only > 0 {
+            eprintln!("  Skipped (read-only): {}", tally.read_only);
+        }
+        eprintln!("  Errors: {}", tally.errors);
+        if not_processed > 0 {
+            eprintln!(
+                "  Not processed: {} (use --continue-on-error to process them)",
+                not_processed
+            );
+        }
+        if output.verbosity == Verbosity::Verbose {
+            eprintln!("  Time: {:.2?}", start.elapsed());
+        }
+    }
+
+    if args.stats {
+        stats.print(start.elapsed());
+    }
+
+    if let Some(cache) = &settings.cache {
+        if let Err(err) = cache.save() {
+            eprintln!("Error: Failed to write the cache: {}", err);
+            exit = exit.max(Exit::Io);
+        }
+    }
+
+    if !restage.is_empty() {
+        let staged = std::process::Command::new("git").args(["add", "--"]).args(&restage).status();
+        if !staged.is_ok_and(|status| status.success()) {
+            eprintln!("Error: Failed to stage the sorted files");
+            exit = exit.max(Exit::Io);
+        }
+    }
+
+    if settings.check && tally.unsorted > 0 {
+        exit = exit.max(Exit::Unsorted);
+    }
+    exit
+}
+
+/// The report entry for one file, with a document reporter.
+fn report_entry(
+    path: &Path,
+    result: Result<Processed, FileError>,
+    settings: &Settings,
+    tally: &mut Tally,
+) -> Entry {
+    let mut entry = Entry {
+        path: path.display().to_string(),
+        changed: false,
+        unsorted: false,
+        diff: None,
+        read_only: false,
+        error: None,
+    };
+    match result {
+        Ok(Processed { changed, diff, read_only, .. }) => {
+            entry.changed = changed;
+            entry.unsorted = changed && (settings.check || settings.dry_run || read_only);
+            entry.read_only = read_only;
+            entry.diff = diff;
+            if read_only {
+                tally.read_only += 1;
+            } else if entry.unsorted {
+                tally.unsorted += 1;
+            } else {
+                tally.sorted += 1;
+            }
+        }
+        Err(err) => entry.error = Some(err.message),
+    }
+    entry
+}
+
+/// Lists one file on stdout if it needs sorting, with `--list-different`.
+#[allow(clippy::print_stderr)]
+fn list_result(
+    path: &Path,
+    result: Result<Processed, FileError>,
+    output: Output,
+    tally: &mut Tally,
+) {
+    match result {
+        Ok(Processed { changed: true, .. }) => {
+            tally.unsorted += 1;
+            // A closed pipe (e.g. `| head`) just ends the listing early.
+            let _ = writeln!(io::stdout(), "{}", path.display());
+        }
+        Ok(_) => {}
+        Err(err) => eprintln!(
+            "{} {}: {}",
+            output.colors.stderr(RED, "✗ Error processing"),
+            path.display(),
+            err
+        ),
+    }
+}
+
+/// Prints the line for one file on stderr, with the human reporter.
+#[allow(clippy::print_stderr)]
+fn print_result(
+    path: &Path,
+    result: Result<Processed, FileError>,
+    elapsed: Duration,
+    settings: &Settings,
+    output: Output,
+    tally: &mut Tally,
+) {
+    let Output { verbosity, colors, .. } = output;
+    let timing =
+        if verbosity == Verbosity::Verbose { format!(" ({:.2?})", elapsed) } else { String::new() };
+    match result {
+        Ok(Processed { changed, diff, .. }) if settings.check => {
+            if changed {
+                tally.unsorted += 1;
+                eprintln!("{} {}{}", colors.stderr(RED, "✗ Not sorted:"), path.display(), timing);
+                if let Some(diff) = diff {
+                    print_diff(&diff, colors);
+                }
+            } else if verbosity == Verbosity::Verbose {
+                eprintln!(
+                    "{} {}{}",
+                    colors.stderr(GREEN, "✓ Already sorted:"),
+                    path.display(),
+                    timing
+                );
+            }
+        }
+        Ok(Processed { changed, diff, .. }) if settings.dry_run => {
+            if changed {
+                tally.unsorted += 1;
+                if verbosity != Verbosity::Quiet {
+                    eprintln!(
+                        "{} {}{}",
+                        colors.stderr(YELLOW, "~ Would sort:"),
+                        path.display(),
+                        timing
+                    );
+                }
+                if let Some(diff) = diff {
+                    print_diff(&diff, colors);
+                }
+            } else if verbosity == Verbosity::Verbose {
+                eprintln!(
+                    "{} {}{}",
+                    colors.stderr(GREEN, "✓ Already sorted:"),
+                    path.display(),
+                    timing
+                );
+            }
+        }
+        Ok(Processed { declined: true, .. }) => {
+            tally.declined += 1;
+            if verbosity == Verbosity::Verbose {
+                eprintln!("- Skipped: {} (declined)", path.display());
+            }
+        }
+        Ok(Processed { read_only: true, .. }) => {
+            tally.read_only += 1;
+            eprintln!(
+                "{} {} (use --force to rewrite it){}",
+                colors.stderr(YELLOW, "! Skipped read-only file:"),
+                path.display(),
+                timing
+            );
+        }
+        Ok(Processed { changed, diff, .. }) => {
+            tally.sorted += 1;
+            if verbosity == Verbosity::Verbose && !changed {
+                eprintln!(
+                    "{} {}{}",
+                    colors.stderr(GREEN, "✓ Already sorted:"),
+                    path.display(),
+                    timing
+                );
+            } else if verbosity != Verbosity::Quiet {
+                eprintln!("{} {}{}", colors.stderr(GREEN, "✓ Sorted:"), path.display(), timing);
+            }
+            if let Some(diff) = diff {
+                print_diff(&diff, colors);
+            }
+        }
+        Err(err) => {
+            eprintln!("{} {}: {}", colors.stderr(RED, "✗ Error processing"), path.display(), err)
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Sort options from config files and the command line.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use sort_package_json::{Config, FieldOrder, Indent, SortOptions, find_config_file};
+
+/// Sort options given on the command line, which override the config file.
+pub(crate) struct Overrides {
+    pub(crate) field_order: Option<Vec<String>>,
+    pub(crate) indent: Option<Indent>,
+}
+
+impl Overrides {
+    pub(crate) fn apply(&self, options: &mut SortOptions) {
+        if let Some(fields) = &self.field_order {
+            options.field_order = FieldOrder::Custom(fields.clone());
+        }
+        if let Some(indent) = self.indent {
+            options.indent = indent;
+        }
+    }
+}
+
+/// Sort options for documents anywhere, for long-running modes: each document's nearest
+/// config, loaded once (and again when edited), with the command line overrides on top.
+pub(crate) struct OptionsCache {
+    overrides: Overrides,
+    /// Options for each config file (an empty path for none), with the config's modification
+    /// time.
+    configs: Mutex<HashMap<PathBuf, LoadedConfig>>,
+}
+
+/// A config's modification time when loaded, and the options it gave.
+type LoadedConfig = (Option<SystemTime>, Result<SortOptions, String>);
+
+impl OptionsCache {
+    pub(crate) fn new(overrides: Overrides) -> Self {
+        Self { overrides, configs: Mutex::default() }
+    }
+
+    /// The options for `path`: its nearest config with the overrides on top.
+    pub(crate) fn get(&self, path: &Path) -> Result<SortOptions, String> {
+        let config = path.parent().and_then(find_config_file).unwrap_or_default();
+        let modified = fs::metadata(&config).and_then(|meta| meta.modified()).ok();
+        let mut configs = self.configs.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((loaded, options)) = configs.get(&config) {
+            if *loaded == modified {
+                return options.clone();
+            }
+        }
+        let options = if config.as_os_str().is_empty() {
+            Ok(SortOptions::default())
+        } else {
+            Config::from_file(&config)
+                .map(|config| config.options)
+                .map_err(|err| format!("{}: {}", config.display(), err))
+        };
+        let options = options.map(|mut options| {
+            self.overrides.apply(&mut options);
+            options
+        });
+        configs.insert(config, (modified, options.clone()));
+        options
+    }
+}
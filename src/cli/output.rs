@@ -0,0 +1,137 @@
+//! Terminal output: colors, verbosity, diffs, progress, and `--stats`.
+
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ColorChoice {
+    /// Color terminals, unless the NO_COLOR environment variable is set.
+    Auto,
+    Always,
+    Never,
+}
+
+/// ANSI SGR codes.
+const BOLD: &str = "1";
+pub(crate) const RED: &str = "31";
+pub(crate) const GREEN: &str = "32";
+pub(crate) const YELLOW: &str = "33";
+const CYAN: &str = "36";
+
+/// Whether to color stdout (diffs) and stderr (status lines).
+#[derive(Clone, Copy)]
+pub(crate) struct Colors {
+    stdout: bool,
+    stderr: bool,
+}
+
+impl Colors {
+    pub(crate) fn new(choice: ColorChoice) -> Self {
+        match choice {
+            ColorChoice::Always => Self { stdout: true, stderr: true },
+            ColorChoice::Never => Self { stdout: false, stderr: false },
+            ColorChoice::Auto => {
+                // https://no-color.org: any non-empty value disables color.
+                let allowed = env::var_os("NO_COLOR").map_or(true, |value| value.is_empty());
+                Self {
+                    stdout: allowed && io::stdout().is_terminal(),
+                    stderr: allowed && io::stderr().is_terminal(),
+                }
+            }
+        }
+    }
+
+    /// `text` for stderr, wrapped in the SGR `code` when stderr is colored.
+    pub(crate) fn stderr(self, code: &str, text: &str) -> String {
+        if self.stderr { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text.to_string() }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Verbosity {
+    /// Only problems: errors and, with `--check`, unsorted files.
+    Quiet,
+    Normal,
+    /// Also unchanged and skipped files, and timings.
+    Verbose,
+}
+
+/// Performance figures for `--stats`.
+#[derive(Default)]
+pub(crate) struct Stats {
+    /// Processing time of each file.
+    pub(crate) times: Vec<Duration>,
+    /// Size of the files read.
+    pub(crate) bytes: usize,
+    /// Files that were already sorted.
+    pub(crate) unchanged: usize,
+}
+
+impl Stats {
+    #[allow(clippy::print_stderr)]
+    pub(crate) fn print(mut self, wall_time: Duration) {
+        self.times.sort_unstable();
+        eprintln!("\nStats:");
+        eprintln!("  Wall time: {:.2?}", wall_time);
+        if !self.times.is_empty() {
+            eprintln!(
+                "  Time per file: p50 {:.2?}, p95 {:.2?}",
+                self.percentile(50),
+                self.percentile(95)
+            );
+        }
+        eprintln!("  Bytes processed: {}", self.bytes);
+        eprintln!("  Unchanged: {}", self.unchanged);
+    }
+
+    /// The nearest-rank percentile of the sorted, non-empty `times`.
+    fn percentile(&self, p: usize) -> Duration {
+        let rank = (self.times.len() * p).div_ceil(100);
+        self.times[rank.saturating_sub(1)]
+    }
+}
+
+/// Redraws a `processed/total` line on stderr every 100ms until `finished`, then clears it.
+/// Runs that finish within 100ms never show it.
+#[allow(clippy::print_stderr)]
+pub(crate) fn show_progress(done: &AtomicUsize, total: usize, finished: &AtomicBool) {
+    let mut shown = false;
+    loop {
+        thread::park_timeout(Duration::from_millis(100));
+        if finished.load(Ordering::Relaxed) {
+            break;
+        }
+        eprint!("\r\x1b[2KProcessed {}/{} files", done.load(Ordering::Relaxed), total);
+        shown = true;
+    }
+    if shown {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+/// Prints a unified diff on stdout, colored per `colors`.
+pub(crate) fn print_diff(diff: &str, colors: Colors) {
+    let mut out = io::stdout().lock();
+    for line in diff.lines() {
+        let code = match line.as_bytes().first() {
+            _ if !colors.stdout => None,
+            Some(b'@') => Some(CYAN),
+            _ if line.starts_with("---") || line.starts_with("+++") => Some(BOLD),
+            Some(b'-') => Some(RED),
+            Some(b'+') => Some(GREEN),
+            _ => None,
+        };
+        // The diff is informational; ignore write errors such as a closed pipe.
+        let _ = match code {
+            Some(code) => writeln!(out, "\x1b[{}m{}\x1b[0m", code, line),
+            None => writeln!(out, "{}", line),
+        };
+    }
+}
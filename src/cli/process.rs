@@ -0,0 +1,329 @@
+//! Sorting files, one at a time or in parallel.
+
+use std::{
+    fmt, fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    Exit,
+    cache::Cache,
+    output::{Colors, print_diff, show_progress},
+    write::{make_writable, write_atomic},
+};
+#[cfg(feature = "json5")]
+use sort_package_json::sort_package_json5;
+use sort_package_json::{
+    SortError, SortOptions, SortOutcome, sort_package_json_with_outcome, unified_diff,
+};
+
+/// Why a file could not be processed.
+pub(crate) struct FileError {
+    /// The exit status this error leads to.
+    pub(crate) code: Exit,
+    pub(crate) message: String,
+}
+
+impl FileError {
+    pub(crate) fn read(err: io::Error) -> Self {
+        Self { code: Exit::Io, message: format!("Failed to read: {}", err) }
+    }
+
+    pub(crate) fn write(err: io::Error) -> Self {
+        Self { code: Exit::Io, message: format!("Failed to write: {}", err) }
+    }
+
+    pub(crate) fn backup(err: io::Error) -> Self {
+        Self { code: Exit::Io, message: format!("Failed to write backup: {}", err) }
+    }
+
+    pub(crate) fn sort(err: SortError) -> Self {
+        let code = if matches!(err, SortError::Io(_)) { Exit::Io } else { Exit::Parse };
+        Self { code, message: format!("Failed to parse JSON: {}", err) }
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// What to do with each file.
+#[derive(Default)]
+pub(crate) struct Settings {
+    /// Report unsorted files instead of writing them.
+    pub(crate) check: bool,
+    /// Produce a unified diff of each change.
+    pub(crate) diff: bool,
+    /// Report files that would change instead of writing them, without failing.
+    pub(crate) dry_run: bool,
+    /// Suffix of the file the original is saved to before a file is overwritten.
+    pub(crate) backup: Option<String>,
+    /// Rewrite read-only files.
+    pub(crate) force: bool,
+    /// How to sort, from the config file.
+    pub(crate) options: SortOptions,
+    /// Files known to be sorted, with `--cache`.
+    pub(crate) cache: Option<Cache>,
+}
+
+/// The result of sorting one file.
+pub(crate) struct Processed {
+    /// Whether the sorted output differs from the file.
+    pub(crate) changed: bool,
+    /// The change as a unified diff, with `--diff`.
+    pub(crate) diff: Option<String>,
+    /// Size of the file.
+    pub(crate) bytes: usize,
+    /// Whether the file needed sorting but was left alone for being read-only.
+    pub(crate) read_only: bool,
+    /// Whether the file needed sorting but the change was declined with `--interactive`.
+    pub(crate) declined: bool,
+}
+
+/// How a file was processed (or why it could not be), and how long that took.
+pub(crate) type FileResult = (Result<Processed, FileError>, Duration);
+
+/// Processes `files` on up to `threads` threads, returning each processed file's result and
+/// processing time in order. With `fail_fast`, no more files are started once one fails, so
+/// later files may be missing from the results. With `progress`, a count of processed files
+/// is shown on stderr meanwhile.
+pub(crate) fn process_files<'a>(
+    files: &'a [PathBuf],
+    settings: &Settings,
+    threads: usize,
+    progress: bool,
+    fail_fast: bool,
+) -> Vec<(&'a Path, FileResult)> {
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let finished = AtomicBool::new(false);
+    let mut results: Vec<(usize, FileResult)> = thread::scope(|scope| {
+        let progress =
+            progress.then(|| scope.spawn(|| show_progress(&done, files.len(), &finished)));
+        let workers: Vec<_> = (0..threads.min(files.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    while !(fail_fast && failed.load(Ordering::Relaxed)) {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file_path) = files.get(i) else { break };
+                        let start = Instant::now();
+                        let result = process_file(file_path, settings);
+                        if result.is_err() {
+                            failed.store(true, Ordering::Relaxed);
+                        }
+                        results.push((i, (result, start.elapsed())));
+                        done.fetch_add(1, Ordering::Relaxed);
+                    }
+                    results
+                })
+            })
+            .collect();
+        let joined: Vec<_> = workers.into_iter().map(|worker| worker.join()).collect();
+        // Stop the progress line before a worker's panic is resumed, or the scope would wait
+        // for it forever.
+        finished.store(true, Ordering::Relaxed);
+        if let Some(progress) = progress {
+            progress.thread().unpark();
+        }
+        joined
+            .into_iter()
+            .flat_map(|worker| worker.unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(i, result)| (files[i].as_path(), result)).collect()
+}
+
+/// Processes `files` one at a time for `--interactive`, showing the diff of each file that
+/// needs sorting and asking on stderr whether to write it. Answers are read from stdin: `y`
+/// sorts the file, `n` skips it, `a` sorts it and every later file, and `q` skips it and
+/// every later file.
+#[allow(clippy::print_stderr)]
+pub(crate) fn process_interactively<'a>(
+    files: &'a [PathBuf],
+    settings: &Settings,
+    colors: Colors,
+) -> Vec<(&'a Path, FileResult)> {
+    let preview = Settings {
+        diff: true,
+        dry_run: true,
+        force: settings.force,
+        options: settings.options.clone(),
+        ..Settings::default()
+    };
+    let mut answers = io::stdin().lock().lines();
+    // The answer for every remaining file, once `a` or `q` is given.
+    let mut all = None;
+    files
+        .iter()
+        .map(|file_path| {
+            let start = Instant::now();
+            let result = match process_file(file_path, &preview) {
+                Ok(Processed { changed: true, diff, bytes, .. }) => {
+                    let sort = match all {
+                        Some(sort) => sort,
+                        None => {
+                            if let Some(diff) = &diff {
+                                print_diff(diff, colors);
+                            }
+                            match ask(file_path, &mut answers) {
+                                'a' => *all.insert(true),
+                                'q' => *all.insert(false),
+                                answer => answer == 'y',
+                            }
+                        }
+                    };
+                    if sort {
+                        process_file(file_path, settings)
+                    } else {
+                        Ok(Processed {
+                            changed: true,
+                            diff: None,
+                            bytes,
+                            read_only: false,
+                            declined: true,
+                        })
+                    }
+                }
+                other => other,
+            };
+            (file_path.as_path(), (result, start.elapsed()))
+        })
+        .collect()
+}
+
+/// Asks whether to sort `path`, returning `y`, `n`, `a`, or `q`. End of input quits.
+#[allow(clippy::print_stderr)]
+fn ask(path: &Path, answers: &mut impl Iterator<Item = io::Result<String>>) -> char {
+    loop {
+        eprint!("Sort {}? [y,n,a,q] ", path.display());
+        let Some(Ok(answer)) = answers.next() else { return 'q' };
+        if let [answer @ (b'y' | b'n' | b'a' | b'q')] = answer.trim().as_bytes() {
+            return char::from(*answer);
+        }
+        eprintln!(
+            "y: sort this file, n: skip it, a: sort this and all later files, q: skip this and \
+             all later files"
+        );
+    }
+}
+
+/// Sorts `contents` (stdin, or the file at `path`) to `out`, returning whether the input
+/// was unsorted. With `check`, nothing is written; with `diff`, the diff is written instead
+/// of the sorted document, labeled with `path`. An `ignored` input is passed through
+/// unchanged.
+pub(crate) fn print_sorted(
+    contents: &str,
+    out: &mut Vec<u8>,
+    settings: &Settings,
+    path: Option<&Path>,
+    ignored: bool,
+    colors: Colors,
+) -> Result<bool, FileError> {
+    if ignored {
+        if !settings.check && !settings.diff {
+            out.extend_from_slice(contents.as_bytes());
+        }
+        return Ok(false);
+    }
+
+    let outcome = sort_manifest(path.unwrap_or(Path::new("-")), contents, &settings.options)
+        .map_err(FileError::sort)?;
+
+    if settings.diff {
+        if outcome.changed {
+            let label = path.map_or_else(|| "-".into(), Path::to_string_lossy);
+            let diff = unified_diff(contents, &outcome.output, &label);
+            print_diff(&diff, colors);
+        }
+    } else if !settings.check {
+        out.extend_from_slice(outcome.output.as_bytes());
+    }
+
+    Ok(outcome.changed)
+}
+
+/// Sorts a file. With `check` or `dry_run`, nothing is written; the diff shows the change
+/// needed.
+fn process_file(file_path: &Path, settings: &Settings) -> Result<Processed, FileError> {
+    let contents = fs::read_to_string(file_path).map_err(FileError::read)?;
+    let bytes = contents.len();
+    if settings.cache.as_ref().is_some_and(|cache| cache.is_sorted(file_path, &contents)) {
+        return Ok(Processed {
+            changed: false,
+            diff: None,
+            bytes,
+            read_only: false,
+            declined: false,
+        });
+    }
+
+    let outcome =
+        sort_manifest(file_path, &contents, &settings.options).map_err(FileError::sort)?;
+
+    let diff = (settings.diff && outcome.changed)
+        .then(|| unified_diff(&contents, &outcome.output, &file_path.to_string_lossy()));
+
+    if outcome.changed && !settings.check && !settings.dry_run {
+        let permissions = fs::metadata(file_path).map_err(FileError::read)?.permissions();
+        if permissions.readonly() && !settings.force {
+            return Ok(Processed { changed: true, diff, bytes, read_only: true, declined: false });
+        }
+        if let Some(suffix) = &settings.backup {
+            let mut backup = file_path.as_os_str().to_owned();
+            backup.push(suffix);
+            fs::write(backup, &contents).map_err(FileError::backup)?;
+        }
+        if permissions.readonly() {
+            // Writable while it is replaced, then read-only again, as it was.
+            let mut writable = permissions.clone();
+            make_writable(&mut writable);
+            fs::set_permissions(file_path, writable).map_err(FileError::write)?;
+            let written = write_atomic(file_path, &outcome.output, &contents);
+            fs::set_permissions(file_path, permissions).map_err(FileError::write)?;
+            written.map_err(FileError::write)?;
+        } else {
+            write_atomic(file_path, &outcome.output, &contents).map_err(FileError::write)?;
+        }
+    }
+    if let Some(cache) = &settings.cache {
+        if !outcome.changed || !(settings.check || settings.dry_run) {
+            cache.insert(file_path, &outcome.output);
+        }
+    }
+
+    Ok(Processed { changed: outcome.changed, diff, bytes, read_only: false, declined: false })
+}
+
+/// Sorts the manifest at `path`. A `.json5` or `.jsonc` file is parsed as JSON5 and keeps its
+/// comments.
+#[cfg(feature = "json5")]
+pub(crate) fn sort_manifest(
+    path: &Path,
+    contents: &str,
+    options: &SortOptions,
+) -> Result<SortOutcome, SortError> {
+    match path.extension() {
+        Some(extension) if extension == "json5" || extension == "jsonc" => {
+            sort_package_json5(contents, options)
+        }
+        _ => sort_package_json_with_outcome(contents, options),
+    }
+}
+
+#[cfg(not(feature = "json5"))]
+pub(crate) fn sort_manifest(
+    _path: &Path,
+    contents: &str,
+    options: &SortOptions,
+) -> Result<SortOutcome, SortError> {
+    sort_package_json_with_outcome(contents, options)
+}
@@ -0,0 +1,197 @@
+//! The document reporters (`--reporter json|checkstyle|junit|sarif`).
+
+use clap::ValueEnum;
+use serde_json::json;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Reporter {
+    /// Per-file lines and a summary on stderr.
+    Human,
+    /// A single JSON document on stdout: `{ "files": [{ "path", "changed", "error" }] }`.
+    Json,
+    /// Checkstyle XML on stdout, with an error for each unsorted or broken file.
+    Checkstyle,
+    /// JUnit XML on stdout, with a test case per file.
+    Junit,
+    /// SARIF on stdout, for GitHub code scanning, with a result for each unsorted or broken
+    /// file.
+    Sarif,
+    /// The paths of files that would change, one per line on stdout (`--list-different`).
+    #[value(skip)]
+    List,
+}
+
+impl Reporter {
+    /// Whether the reporter prints one document on stdout once every file is processed.
+    pub(crate) fn is_document(self) -> bool {
+        matches!(self, Self::Json | Self::Checkstyle | Self::Junit | Self::Sarif)
+    }
+}
+
+/// One file's result, for the document reporters.
+pub(crate) struct Entry {
+    /// The file, as given or found.
+    pub(crate) path: String,
+    /// Whether sorting changes the file.
+    pub(crate) changed: bool,
+    /// Whether the file needs sorting: it would change under `check` or `dry_run`, or is
+    /// read-only.
+    pub(crate) unsorted: bool,
+    /// Whether the file needed sorting but was skipped for being read-only.
+    pub(crate) read_only: bool,
+    /// The change as a unified diff, with `--diff`.
+    pub(crate) diff: Option<String>,
+    /// Why the file could not be processed.
+    pub(crate) error: Option<String>,
+}
+
+/// A JSON document listing each file's result.
+pub(crate) fn json_report(entries: &[Entry]) -> String {
+    let files: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            json!({
+                "path": entry.path,
+                "changed": entry.changed,
+                "skipped": entry.read_only,
+                "diff": entry.diff,
+                "error": entry.error,
+            })
+        })
+        .collect();
+    json!({ "files": files }).to_string()
+}
+
+/// A Checkstyle report, as read by GitLab CI and Jenkins. Sorted files are listed without
+/// errors.
+pub(crate) fn checkstyle_report(entries: &[Entry]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<checkstyle version=\"4.3\">\n");
+    for entry in entries {
+        let message = match (&entry.error, entry.unsorted) {
+            (Some(error), _) => Some(error.as_str()),
+            (None, true) => Some("package.json is not sorted"),
+            (None, false) => None,
+        };
+        match message {
+            Some(message) => {
+                out.push_str(&format!("  <file name=\"{}\">\n", xml_escape(&entry.path)));
+                out.push_str(&format!(
+                    "    <error line=\"1\" column=\"1\" severity=\"error\" message=\"{}\" source=\"sort-package-json\"/>\n",
+                    xml_escape(message)
+                ));
+                out.push_str("  </file>\n");
+            }
+            None => out.push_str(&format!("  <file name=\"{}\"/>\n", xml_escape(&entry.path))),
+        }
+    }
+    out.push_str("</checkstyle>");
+    out
+}
+
+/// A JUnit report with one test case per file: unsorted files fail, with the diff if
+/// there is one, and broken files are errors.
+pub(crate) fn junit_report(entries: &[Entry]) -> String {
+    let failures = entries.iter().filter(|entry| entry.unsorted).count();
+    let errors = entries.iter().filter(|entry| entry.error.is_some()).count();
+    let counts =
+        format!("tests=\"{}\" failures=\"{}\" errors=\"{}\"", entries.len(), failures, errors);
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!("<testsuites name=\"sort-package-json\" {}>\n", counts));
+    out.push_str(&format!("  <testsuite name=\"sort-package-json\" {}>\n", counts));
+    for entry in entries {
+        let name = xml_escape(&entry.path);
+        if let Some(error) = &entry.error {
+            out.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"sort-package-json\">\n",
+                name
+            ));
+            out.push_str(&format!("      <error message=\"{}\"/>\n", xml_escape(error)));
+            out.push_str("    </testcase>\n");
+        } else if entry.unsorted {
+            out.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"sort-package-json\">\n",
+                name
+            ));
+            out.push_str(&format!(
+                "      <failure message=\"package.json is not sorted\">{}</failure>\n",
+                xml_escape(entry.diff.as_deref().unwrap_or_default())
+            ));
+            out.push_str("    </testcase>\n");
+        } else {
+            out.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"sort-package-json\"/>\n",
+                name
+            ));
+        }
+    }
+    out.push_str("  </testsuite>\n</testsuites>");
+    out
+}
+
+/// A SARIF 2.1.0 log with one run. Unsorted files are `unsorted` results and files that
+/// could not be processed are `invalid` results, both at line 1.
+pub(crate) fn sarif_report(entries: &[Entry]) -> String {
+    let results: Vec<_> = entries
+        .iter()
+        .filter_map(|entry| {
+            let (rule, message) = match (&entry.error, entry.unsorted) {
+                (Some(error), _) => ("invalid", error.as_str()),
+                (None, true) => ("unsorted", "package.json is not sorted"),
+                (None, false) => return None,
+            };
+            Some(json!({
+                "ruleId": rule,
+                "level": "error",
+                "message": { "text": message },
+                "locations": [{
+                    "physicalLocation": {
+                        // SARIF URIs use forward slashes.
+                        "artifactLocation": { "uri": entry.path.replace('\\', "/") },
+                        "region": { "startLine": 1 },
+                    },
+                }],
+            }))
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "sort-package-json",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/oxc-project/sort-package-json",
+                    "rules": [
+                        {
+                            "id": "unsorted",
+                            "shortDescription": { "text": "package.json is not sorted" },
+                        },
+                        {
+                            "id": "invalid",
+                            "shortDescription": { "text": "package.json could not be processed" },
+                        },
+                    ],
+                },
+            },
+            "results": results,
+        }],
+    })
+    .to_string()
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
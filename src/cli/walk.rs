@@ -0,0 +1,138 @@
+//! Finding the files to sort: directory walks, ignore files, globs, and git.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{WalkBuilder, gitignore::Gitignore};
+
+/// Directories of installed or vendored packages, and version control data, which the search
+/// skips whether or not an ignore file lists them.
+pub(crate) const VENDOR_DIRS: &[&str] =
+    &["node_modules", "bower_components", "jspm_packages", ".git", ".hg", ".svn"];
+
+/// Which of the walker's default filters to turn off.
+#[derive(Default)]
+pub(crate) struct WalkOptions {
+    /// Don't respect any ignore files.
+    pub(crate) no_ignore: bool,
+    /// Don't respect git ignore files (`.gitignore`, `.git/info/exclude`, global excludes).
+    pub(crate) no_git_ignore: bool,
+    /// Search the [`VENDOR_DIRS`].
+    pub(crate) include_node_modules: bool,
+    /// Extra ignore files, as canonical paths; respected even with `no_ignore`.
+    pub(crate) ignore_paths: Vec<PathBuf>,
+    /// Include hidden files and directories.
+    pub(crate) hidden: bool,
+    /// Descend into symlinked directories.
+    pub(crate) follow_symlinks: bool,
+    /// How many directories to descend below the root; `None` is unlimited.
+    pub(crate) max_depth: Option<usize>,
+    /// Report entries pruned by the exclude patterns.
+    pub(crate) log_skipped: bool,
+}
+
+/// Walks `root`, honoring ignore files per `options` and pruning anything matching
+/// `excludes`.
+#[allow(clippy::print_stderr)]
+pub(crate) fn walk(root: &Path, excludes: &GlobSet, options: &WalkOptions) -> ignore::Walk {
+    let git_ignore = !options.no_ignore && !options.no_git_ignore;
+    let excludes = excludes.clone();
+    let log_skipped = options.log_skipped;
+    let include_node_modules = options.include_node_modules;
+    let mut builder = WalkBuilder::new(root);
+    for path in &options.ignore_paths {
+        // Validated to exist, and a malformed line only loses that pattern.
+        let _ = builder.add_ignore(path);
+    }
+    builder
+        .hidden(!options.hidden)
+        .follow_links(options.follow_symlinks)
+        // Depth 1 holds the root's own entries.
+        .max_depth(options.max_depth.map(|depth| depth + 1))
+        .parents(!options.no_ignore)
+        .ignore(!options.no_ignore)
+        .git_ignore(git_ignore)
+        .git_global(git_ignore)
+        .git_exclude(git_ignore)
+        .filter_entry(move |e| {
+            // The root was asked for, even if it is one of the vendor directories.
+            if !include_node_modules
+                && e.depth() > 0
+                && e.file_type().is_some_and(|ty| ty.is_dir())
+                && VENDOR_DIRS.iter().any(|name| e.file_name() == *name)
+            {
+                if log_skipped {
+                    eprintln!("- Skipped: {} (vendor directory)", relative(e.path()).display());
+                }
+                return false;
+            }
+            let skip = excludes.is_match(relative(e.path()));
+            if skip && log_skipped {
+                eprintln!("- Skipped: {} (excluded)", relative(e.path()).display());
+            }
+            !skip
+        })
+        .build()
+}
+
+/// The files listed by each of the git `commands`, which print NUL-separated paths relative
+/// to the current directory, as canonical paths. Files that don't exist are left out.
+pub(crate) fn git_files(commands: &[&[&str]]) -> Result<HashSet<PathBuf>, String> {
+    let mut files = HashSet::new();
+    for &args in commands {
+        let output = process::Command::new("git")
+            .args(args)
+            .output()
+            .map_err(|err| format!("Failed to run git: {}", err))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        files.extend(stdout.split('\0').filter_map(|path| fs::canonicalize(path).ok()));
+    }
+    Ok(files)
+}
+
+/// Whether `.ignore` files (and, unless disabled, `.gitignore` files) in the directories
+/// containing `path` exclude it. `path` is absolute and need not exist.
+pub(crate) fn is_ignored(path: &Path, options: &WalkOptions) -> bool {
+    let names: &[&str] = match (options.no_ignore, options.no_git_ignore) {
+        (true, _) => &[],
+        (false, true) => &[".ignore"],
+        (false, false) => &[".ignore", ".gitignore"],
+    };
+    // The nearest directory with a matching rule decides, `.ignore` before `.gitignore`, and
+    // `--ignore-path` files last.
+    let per_directory =
+        path.ancestors().skip(1).flat_map(|dir| names.iter().map(move |name| dir.join(name)));
+    for file in per_directory.chain(options.ignore_paths.iter().cloned()) {
+        let (gitignore, _) = Gitignore::new(file);
+        if !path.starts_with(gitignore.path()) {
+            continue;
+        }
+        let matched = gitignore.matched_path_or_any_parents(path, false);
+        if !matched.is_none() {
+            return matched.is_ignore();
+        }
+    }
+    false
+}
+
+pub(crate) fn glob_set(patterns: &[&str]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        // `*` stops at `/`, as in the shell; `**` crosses directories.
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    }
+    builder.build()
+}
+
+/// The path globs are matched against, without a leading `./`.
+pub(crate) fn relative(path: &Path) -> &Path {
+    path.strip_prefix(".").unwrap_or(path)
+}
@@ -0,0 +1,82 @@
+//! Writing sorted files safely.
+
+use std::{
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+/// Replaces the file at `path` (through any symlink) with `contents` by writing a temporary
+/// file next to it and renaming that over the original, so a crash or full disk never leaves
+/// a truncated file behind. The original's permissions are kept. If the file no longer holds
+/// `original` by then, another tool (such as a package manager) wrote it meanwhile, and it is
+/// left alone rather than losing that write.
+pub(crate) fn write_atomic(path: &Path, contents: &str, original: &str) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let permissions = fs::metadata(&path)?.permissions();
+    let mut temp_name = OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp = path.with_file_name(temp_name);
+    let result = write_synced(&temp, contents, permissions).and_then(|()| {
+        if fs::read_to_string(&path)? != original {
+            return Err(io::Error::other("the file was modified while it was being sorted"));
+        }
+        fs::rename(&temp, &path)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Adds write permission for the owner, as `chmod u+w` does.
+#[cfg(unix)]
+pub(crate) fn make_writable(permissions: &mut fs::Permissions) {
+    use std::os::unix::fs::PermissionsExt;
+    permissions.set_mode(permissions.mode() | 0o200);
+}
+
+/// Clears the read-only attribute.
+#[cfg(not(unix))]
+#[allow(clippy::permissions_set_readonly_false)]
+pub(crate) fn make_writable(permissions: &mut fs::Permissions) {
+    permissions.set_readonly(false);
+}
+
+/// Creates `path` with `contents` and `permissions`, flushed to disk.
+fn write_synced(path: &Path, contents: &str, permissions: fs::Permissions) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    file.set_permissions(permissions)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::write_atomic;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = env::temp_dir().join(format!("sort-package-json-write-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("package.json");
+        fs::write(&file, "original").unwrap();
+
+        write_atomic(&file, "sorted", "original").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "sorted");
+
+        // Another tool wrote the file after it was read: that write is kept.
+        fs::write(&file, "changed meanwhile").unwrap();
+        assert!(write_atomic(&file, "sorted", "original").is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "changed meanwhile");
+        // No temporary file is left behind.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
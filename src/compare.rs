@@ -0,0 +1,80 @@
+use std::cmp::Ordering;
+
+/// Compares strings treating runs of ASCII digits as numbers, so `test:2` sorts before
+/// `test:10`. Strings that compare equal this way (e.g. `a01` vs `a1`) fall back to plain
+/// code point order to keep the result total.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    natural_order(a, b, false).then_with(|| a.cmp(b))
+}
+
+/// [`natural_cmp`] ignoring ASCII case, falling back to [`case_insensitive_cmp`] for
+/// strings that still compare equal.
+pub(crate) fn natural_case_insensitive_cmp(a: &str, b: &str) -> Ordering {
+    natural_order(a, b, true).then_with(|| case_insensitive_cmp(a, b))
+}
+
+fn natural_order(a: &str, b: &str, fold_case: bool) -> Ordering {
+    let (mut a_rest, mut b_rest) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a_rest.first(), b_rest.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (a_num, a_tail) = split_digits(a_rest);
+                let (b_num, b_tail) = split_digits(b_rest);
+                let ordering = cmp_digit_runs(a_num, b_num);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                (a_rest, b_rest) = (a_tail, b_tail);
+            }
+            (Some(x), Some(y)) => {
+                let (x, y) = if fold_case {
+                    (x.to_ascii_lowercase(), y.to_ascii_lowercase())
+                } else {
+                    (*x, *y)
+                };
+                if x != y {
+                    return x.cmp(&y);
+                }
+                (a_rest, b_rest) = (&a_rest[1..], &b_rest[1..]);
+            }
+        }
+    }
+}
+
+fn split_digits(s: &[u8]) -> (&[u8], &[u8]) {
+    let end = s.iter().position(|c| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// Compares two digit runs by numeric value without parsing, so arbitrarily long runs
+/// can't overflow.
+fn cmp_digit_runs(a: &[u8], b: &[u8]) -> Ordering {
+    let trim = |s: &[u8]| -> usize { s.iter().take_while(|&&c| c == b'0').count() };
+    let (a, b) = (&a[trim(a)..], &b[trim(b)..]);
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Compares strings ignoring case, falling back to code point order for strings that only
+/// differ in case so the result stays deterministic.
+pub(crate) fn case_insensitive_cmp(a: &str, b: &str) -> Ordering {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+        .then_with(|| a.cmp(b))
+}
+
+/// Compares strings with the Unicode Collation Algorithm (CLDR root collation), so
+/// accented and non-Latin text sorts the way readers expect rather than by code point.
+#[cfg(feature = "collation")]
+pub(crate) fn unicode_cmp(a: &str, b: &str) -> Ordering {
+    use std::cell::RefCell;
+
+    thread_local! {
+        // `Collator::collate` needs `&mut self` for its internal caches.
+        static COLLATOR: RefCell<feruca::Collator> = RefCell::new(feruca::Collator::default());
+    }
+    COLLATOR.with(|collator| collator.borrow_mut().collate(a, b)).then_with(|| a.cmp(b))
+}
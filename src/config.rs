@@ -0,0 +1,319 @@
+//! Loading [`SortOptions`] from a `.sortpackagejsonrc.json` (or, with the `toml` feature,
+//! `.sortpackagejsonrc.toml`) file.
+//!
+//! Keys mirror the `SortOptions` fields in camelCase; unset keys keep their defaults:
+//!
+//! ```json
+//! {
+//!   "fieldOrder": ["name", "version", "private"],
+//!   "exclude": ["scripts"],
+//!   "indent": 4,
+//!   "lineEnding": "crlf"
+//! }
+//! ```
+//!
+//! `fieldOrder` is `"canonical"`, `"alphabetical"`, or a list of fields to put first.
+//! `exclude` lists fields whose values are left untouched. `indent` is a number of spaces
+//! or `"tab"`. `extends` names one or more configs to start from, either by relative path
+//! or as an installed package, so an organization can share one config across repos.
+//! `ignore` lists glob patterns of files for tools that search directories to skip; it does
+//! not affect sorting.
+//!
+//! The same object can instead live in a `package.json` under the `sortPackageJson` field.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    ExportsMode, FieldOrder, FieldStrategy, Indent, LineEnding, PrivateFieldPlacement, SortError,
+    SortOptions, TrailingNewline,
+};
+
+/// Config file names searched by [`find_config_file`], in order of precedence.
+const CONFIG_FILE_NAMES: &[&str] = &[
+    ".sortpackagejsonrc",
+    ".sortpackagejsonrc.json",
+    ".sortpackagejsonrc.toml",
+    "sort-package-json.toml",
+];
+
+/// The `package.json` field holding a config.
+const PACKAGE_JSON_KEY: &str = "sortPackageJson";
+
+/// A loaded config file.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// The sort options.
+    pub options: SortOptions,
+    /// Glob patterns of files to skip, for tools that search directories for manifests.
+    pub ignore: Vec<String>,
+}
+
+impl Config {
+    /// Loads a config file. Files ending in `.toml` are read as TOML (with the `toml`
+    /// feature), anything else as JSON. A `package.json` is read from its `sortPackageJson`
+    /// field.
+    ///
+    /// # Errors
+    ///
+    /// See [`SortOptions::from_config`].
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SortError> {
+        let mut config = load(path.as_ref(), &mut Vec::new())?;
+        let ignore = match config.remove("ignore") {
+            Some(value) => strings("ignore", &value)?,
+            None => Vec::new(),
+        };
+        let mut options = SortOptions::default();
+        apply_config(&mut options, &config)?;
+        Ok(Self { options, ignore })
+    }
+}
+
+impl SortOptions {
+    /// Loads options from a config file. Files ending in `.toml` are read as TOML (with the
+    /// `toml` feature), anything else as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SortError::Io`] if the file cannot be read and [`SortError::Config`] if it
+    /// is malformed or has an unknown key or invalid value.
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<Self, SortError> {
+        Config::from_file(path).map(|config| config.options)
+    }
+}
+
+/// Finds the config file that applies to `dir`: the first of `.sortpackagejsonrc`,
+/// `.sortpackagejsonrc.json`, `.sortpackagejsonrc.toml`, `sort-package-json.toml`, or a
+/// `package.json` with a `sortPackageJson` field in `dir` or its nearest ancestor that has
+/// one. Pass an absolute `dir` to search every ancestor.
+pub fn find_config_file<P: AsRef<Path>>(dir: P) -> Option<PathBuf> {
+    dir.as_ref().ancestors().find_map(|ancestor| {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| ancestor.join(name))
+            .find(|path| path.is_file())
+            .or_else(|| {
+                let manifest = ancestor.join("package.json");
+                let contents = fs::read_to_string(&manifest).ok()?;
+                let value: Value = serde_json::from_str(&contents).ok()?;
+                value.get(PACKAGE_JSON_KEY).is_some().then_some(manifest)
+            })
+    })
+}
+
+/// Reads a config and merges in everything it `extends`: later bases override earlier ones
+/// key by key, and the config's own keys override them all. `chain` holds the configs
+/// currently being loaded, to reject cycles.
+fn load(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Map<String, Value>, SortError> {
+    let canonical = fs::canonicalize(path)?;
+    if chain.contains(&canonical) {
+        return Err(SortError::Config(format!("circular `extends` at {}", path.display())));
+    }
+    let contents = fs::read_to_string(path)?;
+    let config = if path.extension().is_some_and(|ext| ext == "toml") {
+        parse_toml(&contents)?
+    } else {
+        serde_json::from_str(&contents).map_err(|err| SortError::Config(err.to_string()))?
+    };
+    let config = if path.file_name().is_some_and(|name| name == "package.json") {
+        let Value::Object(mut manifest) = config else {
+            return Err(SortError::Config("package.json must be an object".to_string()));
+        };
+        manifest.remove(PACKAGE_JSON_KEY).ok_or_else(|| {
+            SortError::Config(format!("{} has no `{PACKAGE_JSON_KEY}` field", path.display()))
+        })?
+    } else {
+        config
+    };
+    let Value::Object(mut config) = config else {
+        return Err(SortError::Config("config must be an object".to_string()));
+    };
+
+    let Some(extends) = config.remove("extends") else { return Ok(config) };
+    let bases = match extends {
+        Value::String(base) => vec![base],
+        other => strings("extends", &other)?,
+    };
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut merged = Map::new();
+    chain.push(canonical);
+    for base in bases {
+        merged.extend(load(&resolve_extends(&base, dir)?, chain)?);
+    }
+    chain.pop();
+    merged.extend(config);
+    Ok(merged)
+}
+
+/// Resolves an `extends` entry: a path relative to the extending config, or an installed
+/// package (`my-org-config`, or a file inside one like `my-org-config/strict.json`) found in
+/// `node_modules` next to the config or in any parent directory. A package directory
+/// resolves to its `package.json` `main` entry, falling back to `.sortpackagejsonrc.json`.
+fn resolve_extends(spec: &str, dir: &Path) -> Result<PathBuf, SortError> {
+    if spec.starts_with("./") || spec.starts_with("../") || Path::new(spec).is_absolute() {
+        return Ok(dir.join(spec));
+    }
+    for ancestor in dir.ancestors() {
+        let candidate = ancestor.join("node_modules").join(spec);
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+        if candidate.is_dir() {
+            let main = fs::read_to_string(candidate.join("package.json"))
+                .ok()
+                .and_then(|manifest| serde_json::from_str::<Value>(&manifest).ok())
+                .and_then(|manifest| manifest.get("main")?.as_str().map(str::to_string));
+            return Ok(candidate.join(main.as_deref().unwrap_or(".sortpackagejsonrc.json")));
+        }
+    }
+    Err(SortError::Config(format!("cannot resolve `extends` entry \"{spec}\"")))
+}
+
+#[cfg(feature = "toml")]
+fn parse_toml(contents: &str) -> Result<Value, SortError> {
+    toml::from_str(contents).map_err(|err| SortError::Config(err.to_string()))
+}
+
+#[cfg(not(feature = "toml"))]
+fn parse_toml(_contents: &str) -> Result<Value, SortError> {
+    Err(SortError::Config("TOML config requires the `toml` feature".to_string()))
+}
+
+/// Applies each key of `config` on top of `options`.
+fn apply_config(options: &mut SortOptions, config: &Map<String, Value>) -> Result<(), SortError> {
+    for (key, value) in config {
+        match key.as_str() {
+            "pretty" => options.pretty = boolean(key, value)?,
+            "lineWidth" => {
+                // `null`, as `init` writes it, keeps every container expanded.
+                options.line_width = match value {
+                    Value::Null => None,
+                    _ => Some(integer(key, value)?),
+                };
+            }
+            "indent" => {
+                options.indent = match value {
+                    Value::String(s) if s == "tab" => Indent::Tab,
+                    _ => Indent::Spaces(integer(key, value)?),
+                };
+            }
+            "groupSections" => options.group_sections = boolean(key, value)?,
+            "lineEnding" => {
+                options.line_ending =
+                    variant(key, value, &[("lf", LineEnding::Lf), ("crlf", LineEnding::CrLf)])?;
+            }
+            "trailingNewline" => {
+                options.trailing_newline = variant(
+                    key,
+                    value,
+                    &[
+                        ("prettyOnly", TrailingNewline::PrettyOnly),
+                        ("always", TrailingNewline::Always),
+                        ("never", TrailingNewline::Never),
+                        ("preserve", TrailingNewline::Preserve),
+                    ],
+                )?;
+            }
+            "fieldOrder" => {
+                options.field_order = match value {
+                    Value::Array(_) => FieldOrder::Custom(strings(key, value)?),
+                    _ => variant(
+                        key,
+                        value,
+                        &[
+                            ("canonical", FieldOrder::Canonical),
+                            ("alphabetical", FieldOrder::Alphabetical),
+                        ],
+                    )?,
+                };
+            }
+            "exclude" => {
+                for field in strings(key, value)? {
+                    options.field_strategies.insert(field, FieldStrategy::Preserve);
+                }
+            }
+            "topLevelOnly" => options.top_level_only = boolean(key, value)?,
+            "rejectDuplicateKeys" => options.reject_duplicate_keys = boolean(key, value)?,
+            "lenient" => options.lenient = boolean(key, value)?,
+            "normalize" => options.normalize = boolean(key, value)?,
+            "sortScripts" => options.sort_scripts = boolean(key, value)?,
+            "sortDependencies" => options.sort_dependencies = boolean(key, value)?,
+            "caseInsensitiveDependencies" => {
+                options.case_insensitive_dependencies = boolean(key, value)?;
+            }
+            "dependencyOrder" => options.dependency_order = strings(key, value)?,
+            "sortFiles" => options.sort_files = boolean(key, value)?,
+            "exportsMode" => {
+                options.exports_mode = variant(
+                    key,
+                    value,
+                    &[
+                        ("preserve", ExportsMode::Preserve),
+                        ("groupPaths", ExportsMode::GroupPaths),
+                        ("off", ExportsMode::Off),
+                    ],
+                )?;
+            }
+            "dedupeArrays" => options.dedupe_arrays = boolean(key, value)?,
+            "sortUnknownFields" => options.sort_unknown_fields = boolean(key, value)?,
+            "removeEmptyFields" => options.remove_empty_fields = boolean(key, value)?,
+            "keepEmptyFields" => options.keep_empty_fields = strings(key, value)?,
+            "privateFields" => {
+                options.private_fields = variant(
+                    key,
+                    value,
+                    &[
+                        ("first", PrivateFieldPlacement::First),
+                        ("last", PrivateFieldPlacement::Last),
+                        ("preserve", PrivateFieldPlacement::Preserve),
+                        ("merge", PrivateFieldPlacement::Merge),
+                    ],
+                )?;
+            }
+            "naturalSort" => options.natural_sort = boolean(key, value)?,
+            "unicodeCollation" => options.unicode_collation = boolean(key, value)?,
+            _ => return Err(SortError::Config(format!("unknown key `{key}`"))),
+        }
+    }
+    Ok(())
+}
+
+fn invalid(key: &str, expected: &str) -> SortError {
+    SortError::Config(format!("`{key}` must be {expected}"))
+}
+
+fn boolean(key: &str, value: &Value) -> Result<bool, SortError> {
+    value.as_bool().ok_or_else(|| invalid(key, "a boolean"))
+}
+
+fn integer(key: &str, value: &Value) -> Result<usize, SortError> {
+    value
+        .as_u64()
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| invalid(key, "a non-negative integer"))
+}
+
+fn strings(key: &str, value: &Value) -> Result<Vec<String>, SortError> {
+    let items = value.as_array().ok_or_else(|| invalid(key, "an array of strings"))?;
+    items
+        .iter()
+        .map(|item| item.as_str().map(str::to_string))
+        .collect::<Option<_>>()
+        .ok_or_else(|| invalid(key, "an array of strings"))
+}
+
+fn variant<T: Clone>(key: &str, value: &Value, variants: &[(&str, T)]) -> Result<T, SortError> {
+    variants
+        .iter()
+        .find(|(name, _)| value.as_str() == Some(name))
+        .map(|(_, variant)| variant.clone())
+        .ok_or_else(|| {
+            let names: Vec<String> =
+                variants.iter().map(|(name, _)| format!("\"{name}\"")).collect();
+            invalid(key, &format!("one of {}", names.join(", ")))
+        })
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    duplicates::{DuplicateKey, find_duplicate_keys},
+    lenient::Repair,
+};
+
+/// A non-fatal problem noticed while sorting. The document is still sorted; diagnostics
+/// point at data that was dropped or looks wrong.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    /// A string array such as `keywords` contains a non-string entry. The entry is kept,
+    /// after the sorted strings.
+    NonStringArrayEntry { field: String, value: Value },
+    /// An object repeats a key and only the last value was kept. `line` and `column` are
+    /// 1-based and point at the later occurrence.
+    DuplicateKey { key: String, line: usize, column: usize },
+    /// A well-known field holds a value of an unexpected type or content.
+    SuspiciousValue { field: String, message: String },
+    /// A syntax error was repaired under [`SortOptions::lenient`](crate::SortOptions).
+    /// `line` and `column` are 1-based.
+    Recovered { line: usize, column: usize, message: String },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonStringArrayEntry { field, value } => {
+                write!(f, "non-string entry {value} in `{field}`")
+            }
+            Self::DuplicateKey { key, line, column } => {
+                write!(f, "duplicate key `{key}` at line {line} column {column}, last value kept")
+            }
+            Self::SuspiciousValue { field, message } => write!(f, "`{field}` {message}"),
+            Self::Recovered { line, column, message } => {
+                write!(f, "{message} at line {line} column {column}")
+            }
+        }
+    }
+}
+
+/// Expected JSON types of well-known top-level fields.
+const EXPECTED_TYPES: &[(&str, &str)] = &[
+    ("name", "string"),
+    ("version", "string"),
+    ("description", "string"),
+    ("license", "string"),
+    ("main", "string"),
+    ("module", "string"),
+    ("types", "string"),
+    ("packageManager", "string"),
+    ("private", "boolean"),
+    ("keywords", "array"),
+    ("files", "array"),
+    ("scripts", "object"),
+    ("engines", "object"),
+    ("dependencies", "object"),
+    ("devDependencies", "object"),
+    ("peerDependencies", "object"),
+    ("optionalDependencies", "object"),
+];
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Collects diagnostics for a document given its JSON text and parsed top-level object.
+/// With `lenient`, syntax repairs come first and duplicates are searched in the repaired
+/// text.
+pub(crate) fn collect(json: &str, original: &Map<String, Value>, lenient: bool) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let repaired;
+    let json = if lenient {
+        let repairs;
+        (repaired, repairs) = crate::lenient::repair(json);
+        diagnostics.extend(repairs.into_iter().map(|Repair { line, column, message }| {
+            Diagnostic::Recovered { line, column, message: message.to_string() }
+        }));
+        repaired.as_str()
+    } else {
+        json
+    };
+    diagnostics.extend(
+        find_duplicate_keys(json, false).into_iter().map(|DuplicateKey { key, line, column }| {
+            Diagnostic::DuplicateKey { key, line, column }
+        }),
+    );
+    for &(field, expected) in EXPECTED_TYPES {
+        let Some(value) = original.get(field) else { continue };
+        let actual = type_name(value);
+        if actual != expected {
+            let message = format!("should be a {expected}, found {actual}");
+            diagnostics.push(Diagnostic::SuspiciousValue { field: field.to_string(), message });
+        }
+    }
+    for (field, value) in original {
+        let Value::Array(items) = value else { continue };
+        if items.iter().any(Value::is_string) {
+            diagnostics.extend(items.iter().filter(|item| !item.is_string()).map(|item| {
+                Diagnostic::NonStringArrayEntry { field: field.clone(), value: item.clone() }
+            }));
+        }
+    }
+    if let Some(Value::String(ty)) = original.get("type") {
+        if ty != "module" && ty != "commonjs" {
+            let message = format!("should be \"module\" or \"commonjs\", found {ty:?}");
+            diagnostics.push(Diagnostic::SuspiciousValue { field: "type".to_string(), message });
+        }
+    }
+    diagnostics
+}
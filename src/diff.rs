@@ -0,0 +1,249 @@
+use std::{fmt::Write, ops::Range};
+
+use crate::{SortError, SortOptions, sort_package_json_with_options};
+
+/// Lines of unchanged context around each hunk, as in `diff -u`.
+const CONTEXT: usize = 3;
+
+/// Produces a unified diff from `input` to its sorted form, or `None` if it is already
+/// sorted.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`].
+pub fn diff_package_json(input: &str, options: &SortOptions) -> Result<Option<String>, SortError> {
+    let sorted = sort_package_json_with_options(input, options)?;
+    if sorted == input {
+        return Ok(None);
+    }
+    Ok(Some(unified_diff(input, &sorted, "package.json")))
+}
+
+/// Replacement of `range` (byte offsets into the original input) with `new_text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Byte range of the replaced text in the input. Empty for a pure insertion.
+    pub range: Range<usize>,
+    /// The replacement text. Empty for a pure deletion.
+    pub new_text: String,
+}
+
+/// Sorts `input` and returns the changes as minimal line-level edits rather than a whole-file
+/// replacement, so editors can apply them without losing cursor position or undo history.
+/// Edits are ordered by position and never overlap; applying them from last to first turns
+/// `input` into its sorted form. Already sorted input yields no edits.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`].
+pub fn sort_package_json_edits(
+    input: &str,
+    options: &SortOptions,
+) -> Result<Vec<TextEdit>, SortError> {
+    let sorted = sort_package_json_with_options(input, options)?;
+    let old_lines: Vec<&str> = input.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = sorted.split_inclusive('\n').collect();
+
+    let mut edits: Vec<TextEdit> = Vec::new();
+    let (mut offset, mut i, mut j) = (0, 0, 0);
+    // The edit being built from the current run of non-equal ops.
+    let mut current: Option<TextEdit> = None;
+    for op in edit_script(&old_lines, &new_lines) {
+        if op == Op::Equal {
+            edits.extend(current.take());
+            offset += old_lines[i].len();
+            (i, j) = (i + 1, j + 1);
+            continue;
+        }
+        let edit =
+            current.get_or_insert(TextEdit { range: offset..offset, new_text: String::new() });
+        if op == Op::Delete {
+            edit.range.end += old_lines[i].len();
+            offset += old_lines[i].len();
+            i += 1;
+        } else {
+            edit.new_text.push_str(new_lines[j]);
+            j += 1;
+        }
+    }
+    edits.extend(current);
+    Ok(edits)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Renders a unified diff between `old` and `new`, labelling both sides with `path`, e.g.
+/// to show the change from a file to its sorted form. Identical texts yield only the
+/// `---`/`+++` header.
+pub fn unified_diff(old: &str, new: &str, path: &str) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = edit_script(&old_lines, &new_lines);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "--- a/{path}");
+    let _ = writeln!(out, "+++ b/{path}");
+
+    // Walk the script, tracking the line number each op starts at on both sides.
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut i, mut j) = (0, 0);
+    for &op in &ops {
+        positions.push((i, j));
+        match op {
+            Op::Equal => (i, j) = (i + 1, j + 1),
+            Op::Delete => i += 1,
+            Op::Insert => j += 1,
+        }
+    }
+
+    let mut start = 0;
+    while let Some(first_change) = ops[start..].iter().position(|&op| op != Op::Equal) {
+        let first_change = start + first_change;
+        let hunk_start = first_change.saturating_sub(CONTEXT).max(start);
+
+        // Extend the hunk while the next change is close enough for the contexts to merge.
+        let mut hunk_end = first_change;
+        let mut idx = first_change;
+        while idx < ops.len() {
+            if ops[idx] == Op::Equal {
+                let run = ops[idx..].iter().take_while(|&&op| op == Op::Equal).count();
+                if idx + run == ops.len() || run > 2 * CONTEXT {
+                    hunk_end = (idx + run.min(CONTEXT)).min(ops.len());
+                    break;
+                }
+                idx += run;
+            } else {
+                idx += 1;
+                hunk_end = idx;
+            }
+        }
+
+        let (old_start, new_start) = positions[hunk_start];
+        let old_len = ops[hunk_start..hunk_end].iter().filter(|&&op| op != Op::Insert).count();
+        let new_len = ops[hunk_start..hunk_end].iter().filter(|&&op| op != Op::Delete).count();
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            hunk_range(old_start, old_len),
+            hunk_range(new_start, new_len)
+        );
+        for (&op, &(i, j)) in ops[hunk_start..hunk_end].iter().zip(&positions[hunk_start..]) {
+            let (prefix, line) = match op {
+                Op::Equal => (' ', old_lines[i]),
+                Op::Delete => ('-', old_lines[i]),
+                Op::Insert => ('+', new_lines[j]),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        start = hunk_end;
+    }
+    out
+}
+
+/// Formats a hunk range; empty ranges point at the line before, as GNU diff does.
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Computes a shortest edit script with the linear-space refinement of Myers' O(ND)
+/// algorithm: instead of keeping every frontier to backtrack through, it finds the middle
+/// snake of the optimal path and recurses on both sides of it, in O(N + M) space.
+fn edit_script(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let mut ops = Vec::with_capacity(a.len() + b.len());
+    diff_range(a, b, &mut ops);
+    // The recursion may interleave a run of changes; deletions go first, as in other diffs.
+    for run in ops.split_mut(|&op| op == Op::Equal) {
+        run.sort_unstable_by_key(|&op| op == Op::Insert);
+    }
+    ops
+}
+
+/// Appends the edit script turning `a` into `b` to `ops`.
+fn diff_range(a: &[&str], b: &[&str], ops: &mut Vec<Op>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+    ops.extend(std::iter::repeat(Op::Equal).take(prefix));
+    if a.is_empty() {
+        ops.extend(std::iter::repeat(Op::Insert).take(b.len()));
+    } else if b.is_empty() {
+        ops.extend(std::iter::repeat(Op::Delete).take(a.len()));
+    } else {
+        // With the common ends stripped and both sides non-empty, the distance is at least
+        // 2, so both halves are strictly smaller problems.
+        let (start, end) = middle_snake(a, b);
+        diff_range(&a[..start.0], &b[..start.1], ops);
+        ops.extend(std::iter::repeat(Op::Equal).take(end.0 - start.0));
+        diff_range(&a[end.0..], &b[end.1..], ops);
+    }
+    ops.extend(std::iter::repeat(Op::Equal).take(suffix));
+}
+
+/// The start and end of a snake (a run of equal lines) in the middle of a shortest edit
+/// path from `a` to `b`, found by searching forward from the start and backward from the
+/// end at once until the two searches overlap.
+fn middle_snake(a: &[&str], b: &[&str]) -> ((usize, usize), (usize, usize)) {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let max = (n + m + 1) / 2;
+    // `forward[offset + k]` holds the furthest `x` reached on diagonal `k = x - y`, and
+    // `backward[offset + k]` the same for the reversed inputs, where diagonal `k` is
+    // diagonal `delta - k` of the forward search.
+    let offset = max + 1;
+    let mut forward = vec![0isize; 2 * offset as usize + 1];
+    let mut backward = forward.clone();
+    let at = |k: isize| (offset + k) as usize;
+    let go_down =
+        |v: &[isize], d: isize, k: isize| k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]);
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x =
+                if go_down(&forward, d, k) { forward[at(k + 1)] } else { forward[at(k - 1)] + 1 };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            let back_k = delta - k;
+            if delta % 2 != 0 && (1 - d..d).contains(&back_k) && x + backward[at(back_k)] >= n {
+                return ((x0 as usize, y0 as usize), (x as usize, y as usize));
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if go_down(&backward, d, k) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[(n - 1 - x) as usize] == b[(m - 1 - y) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            let forward_k = delta - k;
+            if delta % 2 == 0 && (-d..=d).contains(&forward_k) && x + forward[at(forward_k)] >= n {
+                let start = ((n - x) as usize, (m - y) as usize);
+                return (start, ((n - x0) as usize, (m - y0) as usize));
+            }
+        }
+    }
+    unreachable!("the searches meet by the time half the edit distance is explored")
+}
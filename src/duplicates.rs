@@ -0,0 +1,82 @@
+//! Duplicate key detection for [`SortOptions::reject_duplicate_keys`](crate::SortOptions)
+//! and [`Diagnostic::DuplicateKey`](crate::Diagnostic::DuplicateKey).
+
+use std::collections::HashSet;
+
+use crate::SortError;
+
+/// A repeated key and the 1-based position of its later occurrence.
+pub(crate) struct DuplicateKey {
+    pub key: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Returns [`SortError::DuplicateKey`] for the first key that repeats within an object.
+pub(crate) fn check_duplicate_keys(json: &str) -> Result<(), SortError> {
+    match find_duplicate_keys(json, true).pop() {
+        Some(DuplicateKey { key, line, column }) => {
+            Err(SortError::DuplicateKey { key, line, column })
+        }
+        None => Ok(()),
+    }
+}
+
+/// Scans an already-validated JSON document for keys that repeat within the same object,
+/// stopping at the first one if `first_only` is set.
+pub(crate) fn find_duplicate_keys(json: &str, first_only: bool) -> Vec<DuplicateKey> {
+    let mut duplicates = Vec::new();
+    // One entry per open container: the keys seen so far for objects, `None` for arrays.
+    let mut stack: Vec<Option<HashSet<String>>> = Vec::new();
+    let mut expect_key = false;
+    let (mut line, mut line_start) = (1, 0);
+    let bytes = json.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => {
+                stack.push(Some(HashSet::new()));
+                expect_key = true;
+            }
+            b'[' => {
+                stack.push(None);
+                expect_key = false;
+            }
+            b'}' | b']' => {
+                stack.pop();
+                expect_key = false;
+            }
+            b',' => expect_key = matches!(stack.last(), Some(Some(_))),
+            b'\n' => {
+                line += 1;
+                line_start = i + 1;
+            }
+            b'"' => {
+                let start = i;
+                i += 1;
+                while bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                // The document already parsed, so the literal is a valid JSON string.
+                let key = if expect_key {
+                    serde_json::from_str::<String>(&json[start..=i]).ok()
+                } else {
+                    None
+                };
+                if let (Some(key), Some(Some(seen))) = (key, stack.last_mut()) {
+                    if let Some(key) = seen.replace(key) {
+                        let column = json[line_start..start].chars().count() + 1;
+                        duplicates.push(DuplicateKey { key, line, column });
+                        if first_only {
+                            break;
+                        }
+                    }
+                }
+                expect_key = false;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    duplicates
+}
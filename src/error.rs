@@ -0,0 +1,68 @@
+use std::{error::Error, fmt, io};
+
+/// Errors returned while sorting a `package.json` document.
+#[derive(Debug)]
+pub enum SortError {
+    /// The input is not valid JSON. `line` and `column` are 1-based.
+    Parse { line: usize, column: usize, message: String },
+    /// An object contains the same key twice. Only reported when
+    /// [`SortOptions::reject_duplicate_keys`](crate::SortOptions::reject_duplicate_keys) is
+    /// set; `line` and `column` (1-based) point at the second occurrence.
+    DuplicateKey { key: String, line: usize, column: usize },
+    /// The input is valid JSON, but the root value is not an object.
+    NotAnObject,
+    /// A config file is malformed or has an unknown key or invalid value.
+    Config(String),
+    /// Reading or writing the document failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse { line, column, message } => {
+                write!(f, "invalid JSON at line {line} column {column}: {message}")
+            }
+            Self::DuplicateKey { key, line, column } => {
+                write!(f, "duplicate key `{key}` at line {line} column {column}")
+            }
+            Self::NotAnObject => f.write_str("package.json root must be an object"),
+            Self::Config(message) => write!(f, "invalid config: {message}"),
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+        }
+    }
+}
+
+impl Error for SortError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse { .. }
+            | Self::DuplicateKey { .. }
+            | Self::NotAnObject
+            | Self::Config(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SortError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SortError {
+    fn from(err: serde_json::Error) -> Self {
+        if err.is_io() {
+            return Self::Io(err.into());
+        }
+        let (line, column) = (err.line(), err.column());
+        // `serde_json`'s `Display` appends " at line X column Y"; strip it since the
+        // position is carried separately.
+        let mut message = err.to_string();
+        if let Some(idx) = message.rfind(" at line ") {
+            message.truncate(idx);
+        }
+        Self::Parse { line, column, message }
+    }
+}
@@ -0,0 +1,118 @@
+//! Pretty printing for [`SortOptions::line_width`](crate::SortOptions) and
+//! [`SortOptions::indent`](crate::SortOptions).
+
+use serde_json::{Map, Value};
+
+struct Printer<'a> {
+    out: String,
+    indent: &'a str,
+    /// `None` always expands non-empty containers.
+    width: Option<usize>,
+    /// Section of each top-level field; a blank line separates fields from different
+    /// sections. Empty disables grouping.
+    sections: &'a [usize],
+}
+
+/// Pretty-prints `obj` like `serde_json::to_writer_pretty`, indenting each level with
+/// `indent`. With a `width`, nested arrays and objects are kept on one line (`["dist"]`,
+/// `{ "node": ">=18" }`) when they fit. The root object is always expanded, with a blank
+/// line wherever `sections` (one entry per top-level field, if not empty) changes.
+pub(crate) fn write_pretty(
+    buf: &mut Vec<u8>,
+    obj: &Map<String, Value>,
+    indent: &str,
+    width: Option<usize>,
+    sections: &[usize],
+) {
+    let mut printer = Printer { out: String::new(), indent, width, sections };
+    printer.write_object(obj, 0);
+    buf.extend_from_slice(printer.out.as_bytes());
+}
+
+impl Printer<'_> {
+    fn write_value(&mut self, value: &Value, depth: usize, column: usize) {
+        match value {
+            Value::Array(arr) if !arr.is_empty() => {
+                if !self.write_inline(value, column) {
+                    self.write_array(arr, depth);
+                }
+            }
+            Value::Object(obj) if !obj.is_empty() => {
+                if !self.write_inline(value, column) {
+                    self.write_object(obj, depth);
+                }
+            }
+            _ => self.out.push_str(&value.to_string()),
+        }
+    }
+
+    /// Writes `value` on one line if it fits, with room for a trailing comma.
+    fn write_inline(&mut self, value: &Value, column: usize) -> bool {
+        let Some(width) = self.width else { return false };
+        let inline = inline(value);
+        let fits = column + inline.chars().count() < width;
+        if fits {
+            self.out.push_str(&inline);
+        }
+        fits
+    }
+
+    fn write_array(&mut self, arr: &[Value], depth: usize) {
+        self.out.push('[');
+        for (i, item) in arr.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            let column = self.newline(depth + 1);
+            self.write_value(item, depth + 1, column);
+        }
+        self.newline(depth);
+        self.out.push(']');
+    }
+
+    fn write_object(&mut self, obj: &Map<String, Value>, depth: usize) {
+        self.out.push('{');
+        for (i, (key, value)) in obj.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+                if depth == 0 && self.sections.get(i) != self.sections.get(i - 1) {
+                    self.out.push('\n');
+                }
+            }
+            let column = self.newline(depth + 1);
+            let key = Value::from(key.as_str()).to_string();
+            self.out.push_str(&key);
+            self.out.push_str(": ");
+            self.write_value(value, depth + 1, column + key.chars().count() + 2);
+        }
+        self.newline(depth);
+        self.out.push('}');
+    }
+
+    /// Starts a new line indented `depth` levels, returning the resulting column.
+    fn newline(&mut self, depth: usize) -> usize {
+        self.out.push('\n');
+        for _ in 0..depth {
+            self.out.push_str(self.indent);
+        }
+        self.indent.len() * depth
+    }
+}
+
+/// Renders `value` on a single line, with spaces after commas and inside object braces.
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Array(arr) if !arr.is_empty() => {
+            let items: Vec<String> = arr.iter().map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(obj) if !obj.is_empty() => {
+            let entries: Vec<String> = obj
+                .iter()
+                .map(|(key, value)| format!("{}: {}", Value::from(key.as_str()), inline(value)))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+        _ => value.to_string(),
+    }
+}
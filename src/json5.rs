@@ -0,0 +1,382 @@
+//! Sorting of `package.json5` and `package.jsonc` manifests that keeps their comments.
+
+use std::{cell::Ref, collections::HashMap, str::Chars};
+
+use json5format::{Comment, ParsedDocument};
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    BOM_STR, LineEnding, SortError, SortOptions, SortOutcome, TrailingNewline, check_features,
+    field_section, lenient, sort_object_keys,
+};
+
+/// Sorts a `package.json5` or `package.jsonc` string, keeping each comment next to the field
+/// or item it annotates.
+///
+/// The input is parsed as JSON5, which also covers JSONC. Fields are ordered and transformed
+/// as by [`sort_package_json_with_outcome`](crate::sort_package_json_with_outcome). Keys and
+/// values that sorting leaves alone keep their spelling (unquoted keys, single quotes, hex
+/// numbers, ...); rewritten ones are written as JSON. The output is always pretty-printed
+/// with one entry per line, so [`SortOptions::pretty`] and [`SortOptions::line_width`] do
+/// not apply, and blank lines are only kept between comments. A repeated key keeps its last
+/// value even with [`SortOptions::reject_duplicate_keys`], as the parser does not report
+/// where keys are. The parser also drops the quotes of keys that are valid identifiers:
+/// they are written back quoted if the input is JSONC (JSON with comments and trailing
+/// commas), and unquoted otherwise.
+///
+/// # Errors
+///
+/// Returns [`SortError::Parse`] if `input` is not valid JSON5 or holds a number JSON cannot
+/// represent, such as `NaN`, and [`SortError::NotAnObject`] if the root value is not an
+/// object.
+pub fn sort_package_json5(input: &str, options: &SortOptions) -> Result<SortOutcome, SortError> {
+    check_features(options)?;
+    let (has_bom, body) =
+        input.strip_prefix(BOM_STR).map_or((false, input), |stripped| (true, stripped));
+    // Comments would keep the `\r` of CRLF line endings; `options.line_ending` restores them.
+    let body = body.replace("\r\n", "\n");
+    let document = ParsedDocument::from_str(&body, None).map_err(parse_error)?;
+    let items: Vec<_> = document.content.items().collect();
+    let [root] = items.as_slice() else {
+        return Err(SortError::Parse {
+            line: 1,
+            column: 1,
+            message: "expected a single value".to_string(),
+        });
+    };
+    let obj = match to_json(root) {
+        Ok(Value::Object(obj)) => obj,
+        Ok(_) => return Err(SortError::NotAnObject),
+        Err(token) => return Err(unrepresentable(token, &body)),
+    };
+
+    let sorted = Value::Object(sort_object_keys(obj, options));
+    let sections: Vec<usize> = match &sorted {
+        Value::Object(obj) if options.group_sections => {
+            obj.keys().map(|key| field_section(key)).collect()
+        }
+        _ => Vec::new(),
+    };
+    // Without its comments and trailing commas, JSONC is plain JSON.
+    let quote_keys = serde_json::from_str::<Value>(&lenient::repair(&body).0).is_ok();
+    let indent = options.indent.unit();
+    let mut printer =
+        Printer { out: String::new(), indent: &indent, sections: &sections, quote_keys };
+    if has_bom {
+        printer.out.push_str(BOM_STR);
+    }
+    printer.write_entry(None, &sorted, Some(root), 0, true);
+    printer.write_comments(document.content.trailing_comments(), 0);
+
+    let mut output = printer.out;
+    let trailing_newline = match options.trailing_newline {
+        TrailingNewline::PrettyOnly | TrailingNewline::Always => true,
+        TrailingNewline::Never => false,
+        TrailingNewline::Preserve => input.ends_with('\n'),
+    };
+    if !trailing_newline {
+        output.pop();
+    }
+    if options.line_ending == LineEnding::CrLf {
+        output = output.replace('\n', "\r\n");
+    }
+    let changed = output != input;
+    Ok(SortOutcome { output, changed })
+}
+
+fn parse_error(err: json5format::Error) -> SortError {
+    match err {
+        json5format::Error::Parse(Some(location), message) => {
+            // Drop the excerpt of the offending line that follows the message.
+            let message = match message.split_once(":\n") {
+                Some((message, _)) => message.to_string(),
+                None => message,
+            };
+            SortError::Parse { line: location.line, column: location.col, message }
+        }
+        err => SortError::Parse { line: 1, column: 1, message: err.to_string() },
+    }
+}
+
+/// The parser does not record where values are, so this points at the first occurrence of
+/// `token`.
+fn unrepresentable(token: String, body: &str) -> SortError {
+    let before = &body[..body.find(&token).unwrap_or(0)];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    SortError::Parse { line, column, message: format!("`{token}` has no JSON equivalent") }
+}
+
+/// Converts a parsed value, or returns the token of a number JSON cannot represent.
+fn to_json(value: &json5format::Value) -> Result<Value, String> {
+    match value {
+        json5format::Value::Primitive { val, .. } => {
+            primitive(val.as_str()).ok_or_else(|| val.as_str().to_string())
+        }
+        json5format::Value::Array { val, .. } => {
+            val.items().map(|item| to_json(&item)).collect::<Result<_, _>>().map(Value::Array)
+        }
+        json5format::Value::Object { val, .. } => {
+            let mut obj = Map::new();
+            for property in val.properties() {
+                obj.insert(property_name(property.name()), to_json(&property.value())?);
+            }
+            Ok(Value::Object(obj))
+        }
+    }
+}
+
+fn property_name(name: &str) -> String {
+    if name.starts_with(['"', '\'']) { unquote(name) } else { name.to_string() }
+}
+
+fn primitive(token: &str) -> Option<Value> {
+    match token {
+        "null" => Some(Value::Null),
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ if token.starts_with(['"', '\'']) => Some(Value::String(unquote(token))),
+        _ => number(token).map(Value::Number),
+    }
+}
+
+/// Parses a JSON5 number, which may be hexadecimal, have a leading `+`, or start or end with
+/// a decimal point. `None` for `NaN`, `Infinity`, and hex numbers that overflow.
+fn number(token: &str) -> Option<Number> {
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, token.strip_prefix('+').unwrap_or(token)),
+    };
+    if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        let n = u64::from_str_radix(hex, 16).ok()?;
+        return if negative {
+            i64::try_from(n).ok().map(|n| Number::from(-n))
+        } else {
+            Some(n.into())
+        };
+    }
+    let mut decimal = String::from(if negative { "-" } else { "" });
+    if digits.starts_with('.') {
+        decimal.push('0');
+    }
+    decimal.push_str(digits.replacen(".e", "e", 1).replacen(".E", "E", 1).trim_end_matches('.'));
+    decimal.parse().ok()
+}
+
+/// Decodes a single- or double-quoted JSON5 string literal.
+fn unquote(token: &str) -> String {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token[1..token.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('v') => out.push('\u{b}'),
+            Some('0') => out.push('\0'),
+            Some('x') => out.push(char::from_u32(code_unit(&mut chars, 2)).unwrap_or('\u{fffd}')),
+            Some('u') => {
+                let mut unit = code_unit(&mut chars, 4);
+                if (0xd800..0xdc00).contains(&unit) {
+                    let mut rest = chars.clone();
+                    if rest.next() == Some('\\') && rest.next() == Some('u') {
+                        let low = code_unit(&mut rest, 4);
+                        if (0xdc00..0xe000).contains(&low) {
+                            unit = 0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00);
+                            chars = rest;
+                        }
+                    }
+                }
+                out.push(char::from_u32(unit).unwrap_or('\u{fffd}'));
+            }
+            // A line continuation.
+            Some('\n' | '\r' | '\u{2028}' | '\u{2029}') | None => {}
+            Some(other) => out.push(other),
+        }
+    }
+    out
+}
+
+/// Reads `digits` hex digits, or returns an invalid code point if they are not hex.
+fn code_unit(chars: &mut Chars<'_>, digits: usize) -> u32 {
+    let hex: String = chars.take(digits).collect();
+    u32::from_str_radix(&hex, 16).unwrap_or(u32::MAX)
+}
+
+struct Printer<'a> {
+    out: String,
+    indent: &'a str,
+    /// Section of each top-level field, see [`SortOptions::group_sections`]. Empty disables
+    /// grouping.
+    sections: &'a [usize],
+    /// Whether keys are always written quoted, as JSONC requires.
+    quote_keys: bool,
+}
+
+impl Printer<'_> {
+    /// Writes an object entry or array item on its own line, preceded by the comments above
+    /// it in the input and followed by the comment at the end of its line. `original` is the
+    /// parsed value it was sorted from, if any.
+    fn write_entry(
+        &mut self,
+        key: Option<&str>,
+        value: &Value,
+        original: Option<&json5format::Value>,
+        depth: usize,
+        last: bool,
+    ) {
+        if let Some(original) = original {
+            self.write_comments(original.comments().before_value(), depth);
+        }
+        self.write_indent(depth);
+        if let Some(key) = key {
+            self.out.push_str(key);
+            self.out.push_str(": ");
+        }
+        self.write_value(value, original, depth);
+        if !last {
+            self.out.push(',');
+        }
+        if let Some(comment) =
+            original.and_then(|original| original.comments().end_of_line().as_deref())
+        {
+            // Later lines of the comment stay aligned under the first.
+            let line_start = self.out.rfind('\n').map_or(0, |i| i + 1);
+            let column = self.out[line_start..].chars().count();
+            for (i, line) in comment.lines().enumerate() {
+                if i > 0 {
+                    self.out.push('\n');
+                    self.out.push_str(&" ".repeat(column));
+                }
+                self.out.push_str(" //");
+                self.out.push_str(line);
+            }
+        }
+        self.out.push('\n');
+    }
+
+    fn write_value(&mut self, value: &Value, original: Option<&json5format::Value>, depth: usize) {
+        match (value, original) {
+            (Value::Object(obj), Some(json5format::Value::Object { val, .. })) => {
+                self.write_object(obj, Some(val), depth);
+            }
+            (Value::Object(obj), _) => self.write_object(obj, None, depth),
+            (Value::Array(arr), Some(json5format::Value::Array { val, .. })) => {
+                self.write_array(arr, Some(val), depth);
+            }
+            (Value::Array(arr), _) => self.write_array(arr, None, depth),
+            (_, Some(json5format::Value::Primitive { val, .. }))
+                if primitive(val.as_str()).as_ref() == Some(value) =>
+            {
+                self.out.push_str(val.as_str());
+            }
+            _ => self.out.push_str(&value.to_string()),
+        }
+    }
+
+    fn write_object(
+        &mut self,
+        obj: &Map<String, Value>,
+        original: Option<&json5format::Object>,
+        depth: usize,
+    ) {
+        let trailing = original.map_or(&[][..], |original| original.trailing_comments());
+        if obj.is_empty() && trailing.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+        // A repeated key keeps its last value, and with it the last spelling and comments.
+        let properties: HashMap<String, _> = original
+            .into_iter()
+            .flat_map(json5format::Object::properties)
+            .map(|property| (property_name(property.name()), property))
+            .collect();
+        self.out.push_str("{\n");
+        for (i, (key, value)) in obj.iter().enumerate() {
+            if i > 0 && depth == 0 && self.sections.get(i) != self.sections.get(i - 1) {
+                self.out.push('\n');
+            }
+            let property = properties.get(key);
+            let name = match property {
+                Some(property) if !self.quote_keys || property.name().starts_with(['"', '\'']) => {
+                    property.name().to_string()
+                }
+                _ => Value::from(key.as_str()).to_string(),
+            };
+            let original = property.map(|property| property.value());
+            let last = i + 1 == obj.len();
+            self.write_entry(Some(&name), value, original.as_deref(), depth + 1, last);
+        }
+        self.write_comments(trailing, depth + 1);
+        self.write_indent(depth);
+        self.out.push('}');
+    }
+
+    fn write_array(&mut self, arr: &[Value], original: Option<&json5format::Array>, depth: usize) {
+        let trailing = original.map_or(&[][..], |original| original.trailing_comments());
+        if arr.is_empty() && trailing.is_empty() {
+            self.out.push_str("[]");
+            return;
+        }
+        // Sorting and deduplication move items, so each takes the comments of the first
+        // unclaimed original item with the same value.
+        let items: Vec<Ref<'_, json5format::Value>> =
+            original.map_or_else(Vec::new, |original| original.items().collect());
+        let mut unclaimed: Vec<Option<Value>> =
+            items.iter().map(|item| to_json(item).ok()).collect();
+        self.out.push_str("[\n");
+        for (i, value) in arr.iter().enumerate() {
+            let index = unclaimed.iter().position(|item| item.as_ref() == Some(value));
+            let original = index.map(|index| {
+                unclaimed[index] = None;
+                &*items[index]
+            });
+            self.write_entry(None, value, original, depth + 1, i + 1 == arr.len());
+        }
+        self.write_comments(trailing, depth + 1);
+        self.write_indent(depth);
+        self.out.push(']');
+    }
+
+    /// Writes `comments` on their own lines, indented `depth` levels.
+    fn write_comments(&mut self, comments: &[Comment], depth: usize) {
+        for comment in comments {
+            match comment {
+                Comment::Line(text) => {
+                    self.write_indent(depth);
+                    self.out.push_str("//");
+                    self.out.push_str(text);
+                }
+                Comment::Block { lines, align } => {
+                    self.write_indent(depth);
+                    self.out.push_str("/*");
+                    for (i, line) in lines.iter().enumerate() {
+                        if i > 0 {
+                            self.out.push('\n');
+                            // Aligned lines were stored relative to the comment's indentation.
+                            if *align && !line.is_empty() {
+                                self.write_indent(depth);
+                            }
+                        }
+                        self.out.push_str(line);
+                    }
+                    self.out.push_str("*/");
+                }
+                Comment::Break => {}
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn write_indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str(self.indent);
+        }
+    }
+}
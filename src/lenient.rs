@@ -0,0 +1,74 @@
+//! Recovery from common syntax slips for [`SortOptions::lenient`](crate::SortOptions).
+
+/// A syntax problem that [`repair`] fixed. `line` and `column` are 1-based.
+pub(crate) struct Repair {
+    pub line: usize,
+    pub column: usize,
+    pub message: &'static str,
+}
+
+/// Blanks out trailing commas and `//` or `/* */` comments, which are the usual slips in
+/// hand-edited manifests. Removed bytes are replaced with spaces (newlines are kept), so
+/// positions in the repaired text match the input.
+pub(crate) fn repair(json: &str) -> (String, Vec<Repair>) {
+    let mut out = json.as_bytes().to_vec();
+    let mut repairs = Vec::new();
+    let position = |offset: usize| {
+        let line_start = json[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line = json[..offset].matches('\n').count() + 1;
+        (line, json[line_start..offset].chars().count() + 1)
+    };
+    let mut record = |offset: usize, message| {
+        let (line, column) = position(offset);
+        repairs.push(Repair { line, column, message });
+    };
+    let blank = |out: &mut [u8], range: std::ops::Range<usize>| {
+        for byte in &mut out[range] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    };
+
+    // Offset of the last comma not yet followed by a value.
+    let mut pending_comma = None;
+    let bytes = json.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                pending_comma = None;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = json[i..].find('\n').map_or(bytes.len(), |len| i + len);
+                record(i, "removed comment");
+                blank(&mut out, i..end);
+                i = end;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = json[i + 2..].find("*/").map_or(bytes.len(), |len| i + 2 + len + 2);
+                record(i, "removed comment");
+                blank(&mut out, i..end);
+                i = end;
+                continue;
+            }
+            b',' => pending_comma = Some(i),
+            b'}' | b']' => {
+                if let Some(comma) = pending_comma.take() {
+                    record(comma, "removed trailing comma");
+                    out[comma] = b' ';
+                }
+            }
+            byte if byte.is_ascii_whitespace() => {}
+            _ => pending_comma = None,
+        }
+        i += 1;
+    }
+    // Only whole characters outside strings were blanked, so the text is still UTF-8.
+    (String::from_utf8(out).unwrap_or_default(), repairs)
+}
@@ -1,5 +1,52 @@
+mod compare;
+mod config;
+mod diagnostics;
+mod diff;
+mod duplicates;
+mod error;
+mod format;
+#[cfg(feature = "json5")]
+mod json5;
+mod lenient;
+mod normalize;
+mod report;
+mod sorted;
+mod sorter;
+pub mod transforms;
+#[cfg(feature = "validation")]
+mod validate;
+mod visitor;
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    io::{Read, Write},
+    sync::Arc,
+};
+
 use serde_json::{Map, Value};
 
+pub use config::{Config, find_config_file};
+pub use diagnostics::Diagnostic;
+pub use diff::{TextEdit, diff_package_json, sort_package_json_edits, unified_diff};
+pub use error::SortError;
+#[cfg(feature = "json5")]
+pub use json5::sort_package_json5;
+pub use report::{RemovedEntry, SortReport, sort_package_json_with_report};
+pub use sorted::SortedPackageJson;
+pub use sorter::{FieldRule, Sorter};
+use transforms::{
+    compare_keys, dedupe_array, sort_array, sort_dependencies_meta, sort_git_hooks, sort_husky,
+    sort_keys_naturally, sort_object_alphabetically, sort_object_by_key_order,
+    sort_object_recursive, sort_overrides, sort_peer_dependencies_meta, sort_people_object,
+    sort_resolutions,
+};
+pub use transforms::{sort_dependencies, sort_exports, sort_scripts};
+#[cfg(feature = "validation")]
+pub use validate::{ValidatedPackageJson, ValidationError, sort_package_json_with_validation};
+pub use visitor::{Visitor, sort_package_json_with_visitor};
+
 /// UTF-8 BOM (`U+FEFF`).
 const BOM_STR: &str = "\u{FEFF}";
 
@@ -8,377 +55,957 @@ const BOM_STR: &str = "\u{FEFF}";
 pub struct SortOptions {
     /// Whether to pretty-print the output JSON.
     pub pretty: bool,
+    /// When set, pretty output keeps nested arrays and objects on one line if they fit
+    /// within this many columns, like prettier (`"files": ["dist"]`,
+    /// `"engines": { "node": ">=18" }`). `None` always expands non-empty containers.
+    pub line_width: Option<usize>,
+    /// Indentation of pretty output.
+    pub indent: Indent,
+    /// Whether pretty output separates the logical groups of top-level fields (metadata,
+    /// people, repository, content, entry points, scripts, dependencies, tool configs, ...)
+    /// with a blank line. Unknown and private fields each form a final group.
+    pub group_sections: bool,
+    /// Line ending of the output.
+    pub line_ending: LineEnding,
+    /// When the output ends with a newline.
+    pub trailing_newline: TrailingNewline,
+    /// How top-level fields are ordered.
+    pub field_order: FieldOrder,
+    /// Whether to fail with [`SortError::DuplicateKey`] when an object repeats a key, instead
+    /// of silently keeping the last value as `serde_json` does.
+    pub reject_duplicate_keys: bool,
+    /// Whether to recover from trailing commas and `//` or `/* */` comments instead of
+    /// failing with [`SortError::Parse`]. The repairs are listed as
+    /// [`Diagnostic::Recovered`] in [`sort_package_json_with_report`]; other syntax errors
+    /// still fail.
+    pub lenient: bool,
+    /// Whether to only order the top-level keys, leaving every value untouched: no nested
+    /// sorting, deduplication, normalization, field strategies, or rule transforms.
+    pub top_level_only: bool,
+    /// Whether to expand shorthand values: a string `bugs` into `{ "url" }`, `author`
+    /// (and `contributors`/`maintainers` entries) from `"Name <email> (url)"` into
+    /// `{ name, email, url }`, and `repository` shorthands such as `user/repo` into
+    /// `{ "type": "git", "url" }`.
+    pub normalize: bool,
     /// Whether to sort the scripts field alphabetically.
     pub sort_scripts: bool,
+    /// Whether to sort dependency fields (`dependencies`, `devDependencies`,
+    /// `bundledDependencies`, `resolutions`, `overrides`, ...) by package name.
+    pub sort_dependencies: bool,
+    /// Whether `dependencies`, `devDependencies`, `peerDependencies`, and
+    /// `optionalDependencies` ignore case when sorting, so `JSONStream` sorts among the
+    /// `j`s. Names differing only in case keep a deterministic order.
+    pub case_insensitive_dependencies: bool,
+    /// Relative order of the dependency-family fields (`dependencies`, `devDependencies`,
+    /// `peerDependencies`, ...). Listed fields come first within the dependency section,
+    /// the rest follow in their default order. Empty keeps the default.
+    pub dependency_order: Vec<String>,
+    /// Whether to apply the `files` transform (deduplication). Entry order is always kept
+    /// because `!` negation patterns depend on it.
+    pub sort_files: bool,
+    /// How the `exports` and `imports` objects are handled. Their key order can decide
+    /// which condition matches first, so the default leaves them untouched.
+    pub exports_mode: ExportsMode,
+    /// Whether to remove duplicate entries from string arrays such as `keywords` and
+    /// `files`. Arrays are still sorted when this is off.
+    pub dedupe_arrays: bool,
+    /// Whether to sort fields without a canonical position alphabetically. When off, they
+    /// keep their original relative order (still after known fields, with private
+    /// `_`-prefixed fields last).
+    pub sort_unknown_fields: bool,
+    /// Whether to drop fields whose value is `{}` or `[]` after sorting, such as an empty
+    /// `devDependencies` left behind by tooling. Off by default.
+    pub remove_empty_fields: bool,
+    /// Fields kept even when empty and `remove_empty_fields` is on.
+    pub keep_empty_fields: Vec<String>,
+    /// Where private (`_`-prefixed) fields such as `_id` or `_resolved` are placed.
+    pub private_fields: PrivateFieldPlacement,
+    /// Custom ordering for alphabetical sections: unknown fields, dependencies, string
+    /// arrays, and other alphabetically sorted objects. `None` compares by code point.
+    pub comparator: Option<KeyComparator>,
+    /// Whether alphabetical sections compare digit runs numerically, so `test:2` sorts
+    /// before `test:10`. Ignored when a `comparator` is set.
+    pub natural_sort: bool,
+    /// Whether alphabetical sections use the Unicode Collation Algorithm instead of code
+    /// point order, so e.g. `été` sorts next to `ete` rather than after `z`. Takes
+    /// precedence over `natural_sort`; ignored when a `comparator` is set. Requires the
+    /// `collation` feature: without it, sorting fails with [`SortError::Config`], while the
+    /// [`transforms`] functions, which do not validate options, ignore this flag.
+    pub unicode_collation: bool,
+    /// Per-field overrides of the built-in value transforms, keyed by top-level field
+    /// name. Overridden fields keep their position in the field order.
+    pub field_strategies: HashMap<String, FieldStrategy>,
 }
 
 impl Default for SortOptions {
     fn default() -> Self {
-        Self { pretty: true, sort_scripts: false }
+        Self {
+            pretty: true,
+            line_width: None,
+            indent: Indent::Spaces(2),
+            group_sections: false,
+            line_ending: LineEnding::Lf,
+            trailing_newline: TrailingNewline::PrettyOnly,
+            field_order: FieldOrder::Canonical,
+            top_level_only: false,
+            reject_duplicate_keys: false,
+            lenient: false,
+            normalize: false,
+            sort_scripts: false,
+            sort_dependencies: true,
+            case_insensitive_dependencies: false,
+            dependency_order: Vec::new(),
+            sort_files: true,
+            exports_mode: ExportsMode::Preserve,
+            dedupe_arrays: true,
+            sort_unknown_fields: true,
+            remove_empty_fields: false,
+            keep_empty_fields: Vec::new(),
+            private_fields: PrivateFieldPlacement::Last,
+            comparator: None,
+            natural_sort: false,
+            unicode_collation: false,
+            field_strategies: HashMap::new(),
+        }
+    }
+}
+
+/// Indentation of pretty-printed output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// The given number of spaces per level.
+    Spaces(usize),
+    /// One tab per level.
+    Tab,
+}
+
+impl Indent {
+    fn unit(self) -> String {
+        match self {
+            Self::Spaces(n) => " ".repeat(n),
+            Self::Tab => "\t".to_string(),
+        }
+    }
+}
+
+/// Line ending of serialized output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`, as committed in many Windows repositories.
+    CrLf,
+}
+
+/// When serialized output ends with a newline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingNewline {
+    /// After pretty output only; compact output has none.
+    PrettyOnly,
+    /// Always, for both pretty and compact output.
+    Always,
+    /// Never.
+    Never,
+    /// Only if the input ended with one.
+    Preserve,
+}
+
+/// How top-level fields are ordered. Value transforms (sorted dependencies, deduplicated
+/// arrays, ...) apply either way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldOrder {
+    /// Known fields in [`default_field_order`], then unknown fields.
+    Canonical,
+    /// Every field alphabetically, ignoring the curated order. Private (`_`-prefixed)
+    /// fields are still placed per [`SortOptions::private_fields`].
+    Alphabetical,
+    /// The listed fields first, in the given order, then [`FieldOrder::Canonical`] for the
+    /// rest.
+    Custom(Vec<String>),
+}
+
+/// How the `exports` and `imports` fields are sorted, from least to most aggressive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportsMode {
+    /// Leave both objects exactly as written.
+    Preserve,
+    /// Sort subpath keys (`"."`, `"./utils"`, ... in `exports`; `"#internal"`, ... in
+    /// `imports`), with `"."` first. Objects holding conditions are never reordered.
+    GroupPaths,
+    /// No special handling: keys are sorted alphabetically at every level, including
+    /// condition keys, which can change which condition matches first.
+    Off,
+}
+
+/// Placement of private (`_`-prefixed) fields in the sorted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateFieldPlacement {
+    /// Before all other fields.
+    First,
+    /// After all other fields.
+    Last,
+    /// At their original position in the input.
+    Preserve,
+    /// Sorted together with unknown fields, as if they had no special prefix.
+    Merge,
+}
+
+/// How a field's value is rewritten, overriding the built-in transform for that field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldStrategy {
+    /// Leave the value untouched.
+    Preserve,
+    /// Sort the object's keys alphabetically.
+    Alphabetical,
+    /// Sort the object's keys alphabetically, recursing into nested objects.
+    Recursive,
+    /// Put the listed keys first, in the given order, followed by the rest alphabetically.
+    KeyOrder(Vec<String>),
+}
+
+impl FieldStrategy {
+    fn apply(&self, value: Value, options: &SortOptions) -> Value {
+        match self {
+            Self::Preserve => value,
+            Self::Alphabetical => transform_value(value, options, sort_object_alphabetically),
+            Self::Recursive => transform_value(value, options, sort_object_recursive),
+            Self::KeyOrder(keys) => {
+                let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+                transform_with_key_order(value, options, &keys)
+            }
+        }
+    }
+}
+
+type CompareFn = dyn Fn(&str, &str) -> Ordering + Send + Sync;
+
+/// A shareable key comparison callback, see [`SortOptions::comparator`].
+#[derive(Clone)]
+pub struct KeyComparator(Arc<CompareFn>);
+
+impl KeyComparator {
+    /// Wraps a comparison function. It should be a total order, like [`Ord::cmp`].
+    pub fn new<F>(compare: F) -> Self
+    where
+        F: Fn(&str, &str) -> Ordering + Send + Sync + 'static,
+    {
+        Self(Arc::new(compare))
+    }
+
+    /// Compares two keys.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+impl fmt::Debug for KeyComparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KeyComparator(..)")
     }
 }
 
+/// The result of sorting a document, along with whether sorting changed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortOutcome {
+    /// The sorted document.
+    pub output: String,
+    /// `false` when `output` is byte-for-byte identical to the input.
+    pub changed: bool,
+}
+
 /// Sorts a `package.json` string with custom options.
+///
+/// # Errors
+///
+/// Returns [`SortError::Parse`] if `input` is not valid JSON, [`SortError::NotAnObject`] if
+/// the root value is not an object, and [`SortError::DuplicateKey`] if
+/// [`SortOptions::reject_duplicate_keys`] is set and an object repeats a key.
 pub fn sort_package_json_with_options(
     input: &str,
     options: &SortOptions,
-) -> Result<String, serde_json::Error> {
-    let (has_bom, body) =
-        input.strip_prefix(BOM_STR).map_or((false, input), |stripped| (true, stripped));
+) -> Result<String, SortError> {
+    SortedPackageJson::parse(input, options)?.to_string(options)
+}
 
-    let value: Value = serde_json::from_str(body)?;
+/// Sorts a `package.json` string and reports whether the output differs from the input, so
+/// callers can skip writing files that are already sorted.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`].
+pub fn sort_package_json_with_outcome(
+    input: &str,
+    options: &SortOptions,
+) -> Result<SortOutcome, SortError> {
+    let output = sort_package_json_with_options(input, options)?;
+    let changed = output != input;
+    Ok(SortOutcome { output, changed })
+}
 
-    let sorted = match value {
-        Value::Object(obj) => Value::Object(sort_object_keys(obj, options)),
-        other => other,
+/// Checks whether a `package.json` string is already sorted, without serializing a sorted
+/// copy. Only key order and value transforms are checked; whitespace and indentation are
+/// not.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`].
+pub fn is_package_json_sorted(input: &str, options: &SortOptions) -> Result<bool, SortError> {
+    let (_, obj) = parse_package_json(input, options)?;
+    let sorted = sort_object_keys(obj.clone(), options);
+    Ok(objects_identical(&obj, &sorted))
+}
+
+/// Sorts a `package.json` string with default options (pretty-printed).
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`].
+pub fn sort_package_json(input: &str) -> Result<String, SortError> {
+    sort_package_json_with_options(input, &SortOptions::default())
+}
+
+/// Sorts a `package.json` string into `out`, replacing its contents. Batch tools can reuse
+/// one buffer across many documents instead of allocating a new `String` for each.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`]. On error `out` is left empty.
+pub fn sort_package_json_into(
+    input: &str,
+    out: &mut String,
+    options: &SortOptions,
+) -> Result<(), SortError> {
+    out.clear();
+    let (format, obj) = parse_package_json(input, options)?;
+    let sorted = sort_object_keys(obj, options);
+    let mut buf = std::mem::take(out).into_bytes();
+    let result = write_package_json_into(&sorted, format, options, &mut buf);
+    if result.is_err() {
+        buf.clear();
+    }
+    // SAFETY: `buf` was emptied by `out.clear()`, and `write_package_json_into` only
+    // appends valid UTF-8.
+    *out = unsafe { String::from_utf8_unchecked(buf) };
+    result
+}
+
+/// Sorts many `package.json` strings at once, for tools that have already collected a
+/// workspace's manifests. Inputs are split across the available CPU cores; results are in
+/// input order, one per input.
+pub fn sort_many(inputs: &[&str], options: &SortOptions) -> Vec<Result<String, SortError>> {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from).min(inputs.len());
+    if threads <= 1 {
+        return inputs.iter().map(|input| sort_package_json_with_options(input, options)).collect();
+    }
+    let chunk_size = inputs.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|input| sort_package_json_with_options(input, options))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        // Sorting does not panic on any input; re-raise if it somehow did.
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Reads a `package.json` document from `reader`, sorts it, and writes the result to
+/// `writer`, e.g. to pipe manifests through process stdio. The whole document is read
+/// before sorting, since field order is only known once every field has been seen.
+///
+/// # Errors
+///
+/// Returns [`SortError::Io`] if reading or writing fails (including input that is not
+/// UTF-8), otherwise see [`sort_package_json_with_options`].
+pub fn sort_from_reader<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    options: &SortOptions,
+) -> Result<(), SortError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    let output = sort_package_json_with_options(&input, options)?;
+    writer.write_all(output.as_bytes())?;
+    Ok(())
+}
+
+/// What serialization needs to remember about the original input.
+#[derive(Debug, Clone, Copy)]
+struct InputFormat {
+    has_bom: bool,
+    /// Input length, used to size the output buffer.
+    len: usize,
+    /// Whether the input ended with a newline, for [`TrailingNewline::Preserve`].
+    trailing_newline: bool,
+}
+
+/// Fails if `options` need a feature this build lacks.
+fn check_features(options: &SortOptions) -> Result<(), SortError> {
+    if options.unicode_collation && !cfg!(feature = "collation") {
+        return Err(SortError::Config(
+            "`unicode_collation` requires the `collation` feature".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Strips an optional BOM and parses the body, returning the input's format.
+fn parse_package_json(
+    input: &str,
+    options: &SortOptions,
+) -> Result<(InputFormat, Map<String, Value>), SortError> {
+    check_features(options)?;
+    let (has_bom, body) =
+        input.strip_prefix(BOM_STR).map_or((false, input), |stripped| (true, stripped));
+    let format = InputFormat { has_bom, len: input.len(), trailing_newline: input.ends_with('\n') };
+    let repaired;
+    let (body, value) = match serde_json::from_str(body) {
+        Ok(value) => (body, value),
+        Err(err) if options.lenient => {
+            repaired = lenient::repair(body).0;
+            // Report the original error if the repairs were not enough.
+            let value = serde_json::from_str(&repaired).map_err(|_| err)?;
+            (repaired.as_str(), value)
+        }
+        Err(err) => return Err(err.into()),
     };
+    let Value::Object(obj) = value else { return Err(SortError::NotAnObject) };
+    if options.reject_duplicate_keys {
+        duplicates::check_duplicate_keys(body)?;
+    }
+    Ok((format, obj))
+}
 
+/// Serializes a sorted document, prepending the BOM if the input had one.
+fn write_package_json(
+    obj: &Map<String, Value>,
+    input: InputFormat,
+    options: &SortOptions,
+) -> Result<String, SortError> {
+    // Sized for the common case where the input is already pretty-printed: output ≈ input
+    // in length. The `+ 16` absorbs the trailing `'\n'` push and minor reformatting slop
+    // without forcing a final realloc.
+    let mut buf: Vec<u8> = Vec::with_capacity(input.len + 16);
+    write_package_json_into(obj, input, options, &mut buf)?;
+    // SAFETY: `write_package_json_into` only appends valid UTF-8 to the empty buffer.
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Appends a serialized sorted document to `buf`.
+fn write_package_json_into(
+    obj: &Map<String, Value>,
+    input: InputFormat,
+    options: &SortOptions,
+    buf: &mut Vec<u8>,
+) -> Result<(), SortError> {
     // Serialize directly into a byte buffer so the (optional) BOM, the JSON body, and the
     // trailing newline are all written into a single allocation. This skips the extra
     // String allocation + copy that `to_string_pretty` followed by manual BOM-prepending
     // would incur.
-    //
-    // Sized for the common case where the input is already pretty-printed: output ≈ input
-    // in length. The `+ 16` absorbs the trailing `'\n'` push and minor reformatting slop
-    // without forcing a final realloc.
-    let mut buf: Vec<u8> = Vec::with_capacity(input.len() + 16);
-    if has_bom {
+    let start = buf.len();
+    if input.has_bom {
         buf.extend_from_slice(BOM_STR.as_bytes());
     }
     if options.pretty {
-        serde_json::to_writer_pretty(&mut buf, &sorted)?;
-        buf.push(b'\n');
+        // serde_json's printer is the fast path for the default layout.
+        if options.line_width.is_none()
+            && options.indent == Indent::Spaces(2)
+            && !options.group_sections
+        {
+            serde_json::to_writer_pretty(&mut *buf, obj)?;
+        } else {
+            let sections: Vec<usize> = if options.group_sections {
+                obj.keys().map(|key| field_section(key)).collect()
+            } else {
+                Vec::new()
+            };
+            let indent = options.indent.unit();
+            format::write_pretty(buf, obj, &indent, options.line_width, &sections);
+        }
     } else {
-        serde_json::to_writer(&mut buf, &sorted)?;
+        serde_json::to_writer(&mut *buf, obj)?;
     }
-    // SAFETY: `serde_json::to_writer{,_pretty}` are contractually required to emit valid
-    // UTF-8 (this is also what `serde_json::to_string_pretty` itself relies on). The BOM
-    // bytes and the trailing `\n` are also valid UTF-8.
-    Ok(unsafe { String::from_utf8_unchecked(buf) })
+    let trailing_newline = match options.trailing_newline {
+        TrailingNewline::PrettyOnly => options.pretty,
+        TrailingNewline::Always => true,
+        TrailingNewline::Never => false,
+        TrailingNewline::Preserve => input.trailing_newline,
+    };
+    if trailing_newline {
+        buf.push(b'\n');
+    }
+    if options.line_ending == LineEnding::CrLf {
+        // Newlines inside strings are escaped, so every `\n` byte is a line break. Expand
+        // in place, back to front, so a reused buffer is not reallocated twice.
+        let newlines = buf[start..].iter().filter(|&&byte| byte == b'\n').count();
+        let len = buf.len();
+        buf.resize(len + newlines, 0);
+        let mut write = buf.len();
+        for read in (start..len).rev() {
+            let byte = buf[read];
+            write -= 1;
+            buf[write] = byte;
+            if byte == b'\n' {
+                write -= 1;
+                buf[write] = b'\r';
+            }
+        }
+    }
+    // `serde_json::to_writer{,_pretty}` are contractually required to emit valid UTF-8
+    // (this is also what `serde_json::to_string_pretty` itself relies on), and
+    // `format::write_pretty` only writes the bytes of a `String`. The BOM bytes, the
+    // trailing `\n`, and the inserted `\r`s are also valid UTF-8.
+    Ok(())
 }
 
-/// Sorts a `package.json` string with default options (pretty-printed).
-pub fn sort_package_json(input: &str) -> Result<String, serde_json::Error> {
-    sort_package_json_with_options(input, &SortOptions::default())
+/// Order-sensitive equality. `Map`'s `PartialEq` ignores key order, which is exactly what
+/// sorting changes.
+fn values_identical(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => objects_identical(a, b),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_identical(a, b))
+        }
+        (a, b) => a == b,
+    }
+}
+
+fn objects_identical(a: &Map<String, Value>, b: &Map<String, Value>) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|((ka, va), (kb, vb))| ka == kb && values_identical(va, vb))
 }
 
 // ===== Value-level transformations ==========================================
 
 #[inline]
-fn transform_value<F>(value: Value, f: F) -> Value
+fn transform_value<F>(value: Value, options: &SortOptions, f: F) -> Value
 where
-    F: FnOnce(Map<String, Value>) -> Map<String, Value>,
+    F: FnOnce(Map<String, Value>, &SortOptions) -> Map<String, Value>,
 {
     match value {
-        Value::Object(o) => Value::Object(f(o)),
+        Value::Object(o) => Value::Object(f(o, options)),
         other => other,
     }
 }
 
 #[inline]
-fn transform_array<F>(value: Value, f: F) -> Value
+fn transform_array<F>(value: Value, options: &SortOptions, f: F) -> Value
 where
-    F: FnOnce(Vec<Value>) -> Vec<Value>,
+    F: FnOnce(Vec<Value>, &SortOptions) -> Vec<Value>,
 {
     match value {
-        Value::Array(arr) => Value::Array(f(arr)),
+        Value::Array(arr) => Value::Array(f(arr, options)),
         other => other,
     }
 }
 
 #[inline]
-fn transform_with_key_order(value: Value, key_order: &[&str]) -> Value {
-    transform_value(value, |o| sort_object_by_key_order(o, key_order))
+fn transform_with_key_order(value: Value, options: &SortOptions, key_order: &[&str]) -> Value {
+    transform_value(value, options, |o, options| sort_object_by_key_order(o, options, key_order))
 }
 
-fn sort_object_alphabetically(mut obj: Map<String, Value>) -> Map<String, Value> {
-    obj.sort_keys();
-    obj
-}
+// ===== Top-level field ordering =============================================
 
-fn sort_object_recursive(mut obj: Map<String, Value>) -> Map<String, Value> {
-    sort_object_recursive_in_place(&mut obj);
-    obj
-}
+/// Declares the canonical order for known top-level `package.json` fields, generating
+/// `DEFAULT_FIELD_ORDER` and `known_field`. For each matched key, `known_field` returns the
+/// field's order index and its value, rewritten by the optional transformation expression
+/// (with the named value, options, and rules bindings in scope) when `transform` is set.
+/// Unknown fields are handed back unchanged.
+macro_rules! declare_field_order {
+    (
+        |$value:ident, $options:ident, $rules:ident|
+        [ $( $idx:literal => $field_name:literal $( => $transform:expr )? ),* $(,)? ]
+    ) => {
+        const DEFAULT_FIELD_ORDER: &[&str] = &[$($field_name),*];
 
-fn sort_object_recursive_in_place(obj: &mut Map<String, Value>) {
-    for value in obj.values_mut() {
-        if let Value::Object(nested) = value {
-            sort_object_recursive_in_place(nested);
-        }
-    }
-    obj.sort_keys();
-}
-
-/// Filters non-strings, sorts ascending, and removes duplicates.
-fn sort_array_unique(mut arr: Vec<Value>) -> Vec<Value> {
-    arr.retain(Value::is_string);
-    // `unwrap` is sound: `retain` above guarantees every element is a string.
-    arr.sort_unstable_by(|a, b| a.as_str().unwrap().cmp(b.as_str().unwrap()));
-    arr.dedup_by(|a, b| a.as_str() == b.as_str());
-    arr
-}
-
-/// Removes duplicate string entries while preserving original order. Used for fields
-/// where order matters (e.g., `files` with `!` negation patterns).
-fn dedupe_array(mut arr: Vec<Value>) -> Vec<Value> {
-    let mut write = 0;
-    for read in 0..arr.len() {
-        let keep = match arr[read].as_str() {
-            Some(s) => !arr[..write].iter().any(|seen| seen.as_str() == Some(s)),
-            None => false,
-        };
-        if keep {
-            if write != read {
-                arr.swap(write, read);
+        fn known_field(
+            key: &str,
+            $value: Value,
+            $options: &SortOptions,
+            $rules: &[Box<dyn FieldRule>],
+            transform: bool,
+        ) -> Result<(usize, Value), Value> {
+            match key {
+                $(
+                    $field_name => Ok((
+                        $idx,
+                        if transform {
+                            declare_field_order!(@value $value $(, $transform)?)
+                        } else {
+                            $value
+                        },
+                    )),
+                )*
+                _ => Err($value),
             }
-            write += 1;
         }
-    }
-    arr.truncate(write);
-    arr
+    };
+    (@value $value:ident) => { $value };
+    (@value $value:ident, $transform:expr) => { $transform };
 }
 
-/// Reorders `obj` so that any keys present in `key_order` appear first (in the given
-/// order), with the remaining keys following alphabetically.
-///
-/// Single-pass classification + merge — avoids `IndexMap::shift_remove`'s O(n) tail-shift
-/// per requested key.
-fn sort_object_by_key_order(obj: Map<String, Value>, key_order: &[&str]) -> Map<String, Value> {
-    let mut known: Vec<Option<(String, Value)>> = (0..key_order.len()).map(|_| None).collect();
-    let mut others: Vec<(String, Value)> = Vec::new();
-
-    for (key, value) in obj {
-        match key_order.iter().position(|kn| *kn == key.as_str()) {
-            Some(idx) => known[idx] = Some((key, value)),
-            None => others.push((key, value)),
-        }
-    }
+declare_field_order!(|value, options, rules| [
+    // Core Package Metadata
+    0 => "$schema",
+    1 => "name",
+    2 => "displayName",
+    3 => "version",
+    4 => "stableVersion",
+    5 => "gitHead",
+    6 => "private",
+    7 => "description",
+    8 => "categories" => transform_array(value, options, sort_array),
+    9 => "keywords" => transform_array(value, options, sort_array),
+    10 => "homepage",
+    11 => "bugs" => transform_with_key_order(normalize::bugs(value, options), options, &["url", "email"]),
+    // License & People
+    12 => "license",
+    13 => "author" => transform_value(normalize::person(value, options), options, sort_people_object),
+    14 => "maintainers" => normalize::people(value, options),
+    15 => "contributors" => normalize::people(value, options),
+    // Repository & Funding
+    16 => "repository" => transform_with_key_order(normalize::repository(value, options), options, &["type", "url"]),
+    17 => "funding" => transform_with_key_order(value, options, &["type", "url"]),
+    18 => "donate" => transform_with_key_order(value, options, &["type", "url"]),
+    19 => "sponsor" => transform_with_key_order(value, options, &["type", "url"]),
+    20 => "qna",
+    21 => "publisher",
+    // Package Content & Distribution
+    22 => "man",
+    23 => "style",
+    24 => "example",
+    25 => "examplestyle",
+    26 => "assets",
+    27 => "bin" => transform_value(value, options, sort_object_alphabetically),
+    28 => "source",
+    29 => "directories" => transform_with_key_order(value, options, &["lib", "bin", "man", "doc", "example", "test"]),
+    30 => "workspaces",
+    31 => "binary" => transform_with_key_order(value, options, &["module_name", "module_path", "remote_path", "package_name", "host"]),
+    32 => "files" => if options.sort_files { transform_array(value, options, dedupe_array) } else { value },
+    33 => "os",
+    34 => "cpu",
+    35 => "libc" => transform_array(value, options, sort_array),
+    // Package Entry Points
+    36 => "type",
+    37 => "sideEffects",
+    38 => "main",
+    39 => "module",
+    40 => "browser",
+    41 => "types",
+    42 => "typings",
+    43 => "typesVersions",
+    44 => "typeScriptVersion",
+    45 => "typesPublisherContentHash",
+    46 => "react-native",
+    47 => "svelte",
+    48 => "unpkg",
+    49 => "jsdelivr",
+    50 => "jsnext:main",
+    51 => "umd",
+    52 => "umd:main",
+    53 => "es5",
+    54 => "esm5",
+    55 => "fesm5",
+    56 => "es2015",
+    57 => "esm2015",
+    58 => "fesm2015",
+    59 => "es2020",
+    60 => "esm2020",
+    61 => "fesm2020",
+    62 => "esnext",
+    63 => "imports" => transform_value(value, options, sort_exports),
+    64 => "exports" => transform_value(value, options, sort_exports),
+    65 => "publishConfig" => transform_value(value, options, |o, options| sort_object_keys_with_rules(o, options, rules)),
+    // Scripts
+    66 => "scripts" => if options.sort_scripts { transform_value(value, options, sort_scripts) } else { value },
+    67 => "betterScripts" => if options.sort_scripts { transform_value(value, options, sort_scripts) } else { value },
+    68 => "wireit" => if options.sort_scripts { transform_value(value, options, sort_scripts) } else { value },
+    // Dependencies
+    69 => "dependencies" => if options.sort_dependencies { transform_value(value, options, sort_dependencies) } else { value },
+    70 => "devDependencies" => if options.sort_dependencies { transform_value(value, options, sort_dependencies) } else { value },
+    71 => "dependenciesMeta" => if options.sort_dependencies { transform_value(value, options, sort_dependencies_meta) } else { value },
+    72 => "peerDependencies" => if options.sort_dependencies { transform_value(value, options, sort_dependencies) } else { value },
+    73 => "peerDependenciesMeta" => if options.sort_dependencies { transform_value(value, options, sort_peer_dependencies_meta) } else { value },
+    74 => "optionalDependencies" => if options.sort_dependencies { transform_value(value, options, sort_dependencies) } else { value },
+    75 => "bundledDependencies" => if options.sort_dependencies { transform_array(value, options, sort_array) } else { value },
+    76 => "bundleDependencies" => if options.sort_dependencies { transform_array(value, options, sort_array) } else { value },
+    77 => "resolutions" => if options.sort_dependencies { transform_value(value, options, sort_resolutions) } else { value },
+    78 => "overrides" => if options.sort_dependencies { transform_value(value, options, sort_overrides) } else { value },
+    // Git Hooks & Commit Tools
+    79 => "husky" => transform_value(value, options, sort_husky),
+    80 => "simple-git-hooks" => transform_value(value, options, sort_git_hooks),
+    81 => "vite-staged",
+    82 => "lint-staged" => transform_value(value, options, sort_keys_naturally),
+    83 => "nano-staged" => transform_value(value, options, sort_keys_naturally),
+    // The array form lists commands in the order they run, so only the object form is sorted.
+    84 => "pre-commit" => transform_value(value, options, sort_object_alphabetically),
+    85 => "commitlint" => transform_value(value, options, sort_object_recursive),
+    // VSCode Extension Specific
+    86 => "l10n",
+    87 => "contributes",
+    88 => "activationEvents" => transform_array(value, options, sort_array),
+    89 => "extensionPack" => transform_array(value, options, sort_array),
+    90 => "extensionDependencies" => transform_array(value, options, sort_array),
+    91 => "extensionKind" => transform_array(value, options, sort_array),
+    92 => "icon",
+    93 => "badges",
+    94 => "galleryBanner",
+    95 => "preview",
+    96 => "markdown",
+    // Build & Tool Configuration
+    97 => "napi" => transform_value(value, options, sort_object_alphabetically),
+    98 => "flat",
+    99 => "config" => transform_value(value, options, sort_object_alphabetically),
+    100 => "nodemonConfig" => transform_value(value, options, sort_object_recursive),
+    101 => "browserify" => transform_value(value, options, sort_object_recursive),
+    102 => "babel" => transform_value(value, options, sort_object_recursive),
+    103 => "browserslist",
+    104 => "xo" => transform_value(value, options, sort_object_recursive),
+    105 => "prettier" => transform_value(value, options, sort_object_recursive),
+    106 => "sortPackageJson" => transform_value(value, options, sort_object_alphabetically),
+    107 => "eslintConfig" => transform_value(value, options, sort_object_recursive),
+    108 => "eslintIgnore",
+    109 => "standard" => transform_value(value, options, sort_object_recursive),
+    110 => "npmpkgjsonlint",
+    111 => "npmPackageJsonLintConfig",
+    112 => "npmpackagejsonlint",
+    113 => "release",
+    // Only sorts top-level keys: `replaceText` maps regexes to replacements applied sequentially in key order
+    114 => "auto-changelog" => transform_value(value, options, sort_object_alphabetically),
+    // Only sorts top-level keys: `plugins` in object form runs plugins in key order
+    115 => "remarkConfig" => transform_value(value, options, sort_object_alphabetically),
+    116 => "stylelint" => transform_value(value, options, sort_object_recursive),
+    117 => "typescript" => transform_value(value, options, sort_object_recursive),
+    118 => "typedoc" => transform_value(value, options, sort_object_recursive),
+    // Only sorts top-level keys: `exports` values may be pass-through conditional exports
+    119 => "tshy" => transform_value(value, options, sort_object_alphabetically),
+    120 => "tsdown" => transform_value(value, options, sort_object_recursive),
+    121 => "size-limit",
+    // Testing
+    122 => "ava" => transform_value(value, options, sort_object_recursive),
+    // Only sorts top-level keys: nested config like `moduleNameMapper` is order-dependent
+    123 => "jest" => transform_value(value, options, sort_object_alphabetically),
+    124 => "jest-junit",
+    125 => "jest-stare",
+    126 => "mocha" => transform_value(value, options, sort_object_recursive),
+    127 => "nyc" => transform_value(value, options, sort_object_recursive),
+    128 => "c8" => transform_value(value, options, sort_object_recursive),
+    129 => "tap",
+    130 => "tsd" => transform_value(value, options, sort_object_recursive),
+    131 => "typeCoverage" => transform_value(value, options, sort_object_recursive),
+    132 => "oclif" => transform_value(value, options, sort_object_recursive),
+    // Runtime & Package Manager
+    133 => "languageName",
+    134 => "preferGlobal",
+    135 => "devEngines" => transform_value(value, options, sort_object_alphabetically),
+    136 => "engines" => transform_value(value, options, sort_object_alphabetically),
+    137 => "engineStrict",
+    138 => "volta" => transform_value(value, options, sort_object_recursive),
+    139 => "packageManager",
+    140 => "pnpm",
+]);
 
-    others.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+/// The canonical order of known top-level fields. Fields not listed here sort after them.
+pub fn default_field_order() -> &'static [&'static str] {
+    DEFAULT_FIELD_ORDER
+}
 
-    let mut result = Map::with_capacity(known.len() + others.len());
-    for (key, value) in known.into_iter().flatten() {
-        result.insert(key, value);
-    }
-    for (key, value) in others {
-        result.insert(key, value);
+/// Index in [`DEFAULT_FIELD_ORDER`] where each logical group of fields starts, matching the
+/// comments in the table above.
+const SECTION_STARTS: &[usize] = &[0, 12, 16, 22, 36, 66, 69, 79, 86, 97, 122, 133];
+
+/// The logical group of a top-level field, for [`SortOptions::group_sections`]. Unknown
+/// fields come after every known group, private (`_`-prefixed) fields after them.
+fn field_section(key: &str) -> usize {
+    match DEFAULT_FIELD_ORDER.iter().position(|field| *field == key) {
+        Some(idx) => SECTION_STARTS.partition_point(|&start| start <= idx),
+        None if key.starts_with('_') => SECTION_STARTS.len() + 2,
+        None => SECTION_STARTS.len() + 1,
     }
-    result
 }
 
-fn sort_people_object(obj: Map<String, Value>) -> Map<String, Value> {
-    sort_object_by_key_order(obj, &["name", "email", "url"])
+/// Version of the built-in ordering rules. Bump it whenever the field table above or a
+/// transform changes the output for some input.
+const RULESET_VERSION: &str = "14";
+
+/// Identifies the built-in field order and transforms. It changes whenever they change the
+/// output for some input, so callers can cache sorted results keyed on it. Options are not
+/// included; key caches on them separately.
+pub fn ruleset_version() -> &'static str {
+    RULESET_VERSION
 }
 
-// ===== Top-level field ordering =============================================
+/// The dependency-family fields, in default order. [`SortOptions::dependency_order`]
+/// shuffles them among their own slots.
+const DEPENDENCY_FIELDS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "dependenciesMeta",
+    "peerDependencies",
+    "peerDependenciesMeta",
+    "optionalDependencies",
+    "bundledDependencies",
+    "bundleDependencies",
+    "resolutions",
+    "overrides",
+];
 
-/// Declares the canonical order for known top-level `package.json` fields. For each
-/// matched key, the field is bucketed with its order index; an optional transformation
-/// expression (with `value` and `options` in scope) rewrites the value before storage.
-/// Unknown fields fall through to the catch-all arm.
-macro_rules! declare_field_order {
-    (
-        $key:ident, $value:ident, $known:ident, $unknown:ident;
-        [ $( $idx:literal => $field_name:literal $( => $transform:expr )? ),* $(,)? ]
-    ) => {
-        match $key.as_str() {
-            $(
-                $field_name => $known.push((
-                    $idx,
-                    $key,
-                    declare_field_order!(@value $value $(, $transform)?),
-                )),
-            )*
-            _ => $unknown.push(($key, $value)),
+/// Remaps a dependency-family field's order index per [`SortOptions::dependency_order`]:
+/// listed fields take the family's slots first, the rest follow in default order.
+fn dependency_field_index(key: &str, idx: usize, options: &SortOptions) -> usize {
+    if options.dependency_order.is_empty() || !DEPENDENCY_FIELDS.contains(&key) {
+        return idx;
+    }
+    let mut order: Vec<&str> = Vec::with_capacity(DEPENDENCY_FIELDS.len());
+    for field in &options.dependency_order {
+        if DEPENDENCY_FIELDS.contains(&field.as_str()) && !order.contains(&field.as_str()) {
+            order.push(field);
         }
-    };
-    (@value $value:ident) => { $value };
-    (@value $value:ident, $transform:expr) => { $transform };
+    }
+    for field in DEPENDENCY_FIELDS {
+        if !order.contains(field) {
+            order.push(field);
+        }
+    }
+    // `unwrap`s are sound: `order` is a permutation of `DEPENDENCY_FIELDS`, all of which
+    // are in `DEFAULT_FIELD_ORDER` (whose positions are the order indices).
+    let slot = DEPENDENCY_FIELDS[order.iter().position(|field| *field == key).unwrap()];
+    DEFAULT_FIELD_ORDER.iter().position(|field| *field == slot).unwrap()
 }
 
 fn sort_object_keys(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    sort_object_keys_with_rules(obj, options, &[])
+}
+
+/// Whether `value` is an empty object or array that [`SortOptions::remove_empty_fields`]
+/// should drop.
+fn is_removable_empty(key: &str, value: &Value, options: &SortOptions) -> bool {
+    let empty = match value {
+        Value::Object(o) => o.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        _ => false,
+    };
+    empty && options.remove_empty_fields && !options.keep_empty_fields.iter().any(|k| k == key)
+}
+
+fn sort_object_keys_with_rules(
+    obj: Map<String, Value>,
+    options: &SortOptions,
+    rules: &[Box<dyn FieldRule>],
+) -> Map<String, Value> {
     // `known` collects fields with a canonical position; `unknown` collects everything
-    // else, sorted with private (`_`-prefixed) keys after non-private ones.
+    // else, with private (`_`-prefixed) keys placed per `options.private_fields`.
     let mut known: Vec<(usize, String, Value)> = Vec::new();
     let mut unknown: Vec<(String, Value)> = Vec::new();
+    // Original positions of the private fields in `unknown`, for `PrivateFieldPlacement::Preserve`.
+    let mut private_positions: Vec<usize> = Vec::new();
+    let mut push_unknown = |position: usize, key: String, value: Value| {
+        if key.starts_with('_') {
+            private_positions.push(position);
+        }
+        unknown.push((key, value));
+    };
+
+    // Fields claimed by a registered `FieldRule`, merged into `known` after the loop.
+    let mut ruled: Vec<(usize, String, Value)> = Vec::new();
+    // In alphabetical mode every field is still transformed, but sorted as if unknown.
+    let alphabetical_only = options.field_order == FieldOrder::Alphabetical;
+    // Custom-ordered fields take indices `0..custom.len()`; built-in indices are shifted
+    // past them.
+    let custom: &[String] = match &options.field_order {
+        FieldOrder::Custom(order) => order,
+        _ => &[],
+    };
+    let custom_index = |key: &str| custom.iter().position(|field| field == key);
 
-    for (key, value) in obj {
-        declare_field_order!(key, value, known, unknown; [
-            // Core Package Metadata
-            0 => "$schema",
-            1 => "name",
-            2 => "displayName",
-            3 => "version",
-            4 => "stableVersion",
-            5 => "gitHead",
-            6 => "private",
-            7 => "description",
-            8 => "categories" => transform_array(value, sort_array_unique),
-            9 => "keywords" => transform_array(value, sort_array_unique),
-            10 => "homepage",
-            11 => "bugs" => transform_with_key_order(value, &["url", "email"]),
-            // License & People
-            12 => "license",
-            13 => "author" => transform_value(value, sort_people_object),
-            14 => "maintainers",
-            15 => "contributors",
-            // Repository & Funding
-            16 => "repository" => transform_with_key_order(value, &["type", "url"]),
-            17 => "funding" => transform_with_key_order(value, &["type", "url"]),
-            18 => "donate" => transform_with_key_order(value, &["type", "url"]),
-            19 => "sponsor" => transform_with_key_order(value, &["type", "url"]),
-            20 => "qna",
-            21 => "publisher",
-            // Package Content & Distribution
-            22 => "man",
-            23 => "style",
-            24 => "example",
-            25 => "examplestyle",
-            26 => "assets",
-            27 => "bin" => transform_value(value, sort_object_alphabetically),
-            28 => "source",
-            29 => "directories" => transform_with_key_order(value, &["lib", "bin", "man", "doc", "example", "test"]),
-            30 => "workspaces",
-            31 => "binary" => transform_with_key_order(value, &["module_name", "module_path", "remote_path", "package_name", "host"]),
-            32 => "files" => transform_array(value, dedupe_array),
-            33 => "os",
-            34 => "cpu",
-            35 => "libc" => transform_array(value, sort_array_unique),
-            // Package Entry Points
-            36 => "type",
-            37 => "sideEffects",
-            38 => "main",
-            39 => "module",
-            40 => "browser",
-            41 => "types",
-            42 => "typings",
-            43 => "typesVersions",
-            44 => "typeScriptVersion",
-            45 => "typesPublisherContentHash",
-            46 => "react-native",
-            47 => "svelte",
-            48 => "unpkg",
-            49 => "jsdelivr",
-            50 => "jsnext:main",
-            51 => "umd",
-            52 => "umd:main",
-            53 => "es5",
-            54 => "esm5",
-            55 => "fesm5",
-            56 => "es2015",
-            57 => "esm2015",
-            58 => "fesm2015",
-            59 => "es2020",
-            60 => "esm2020",
-            61 => "fesm2020",
-            62 => "esnext",
-            63 => "imports",
-            64 => "exports",
-            65 => "publishConfig" => transform_value(value, |o| sort_object_keys(o, options)),
-            // Scripts
-            66 => "scripts" => if options.sort_scripts { transform_value(value, sort_object_alphabetically) } else { value },
-            67 => "betterScripts" => if options.sort_scripts { transform_value(value, sort_object_alphabetically) } else { value },
-            68 => "wireit" => if options.sort_scripts { transform_value(value, sort_object_alphabetically) } else { value },
-            // Dependencies
-            69 => "dependencies" => transform_value(value, sort_object_alphabetically),
-            70 => "devDependencies" => transform_value(value, sort_object_alphabetically),
-            71 => "dependenciesMeta",
-            72 => "peerDependencies" => transform_value(value, sort_object_alphabetically),
-            73 => "peerDependenciesMeta",
-            74 => "optionalDependencies" => transform_value(value, sort_object_alphabetically),
-            75 => "bundledDependencies" => transform_array(value, sort_array_unique),
-            76 => "bundleDependencies" => transform_array(value, sort_array_unique),
-            77 => "resolutions" => transform_value(value, sort_object_alphabetically),
-            78 => "overrides" => transform_value(value, sort_object_alphabetically),
-            // Git Hooks & Commit Tools
-            79 => "husky" => transform_value(value, sort_object_recursive),
-            80 => "simple-git-hooks",
-            81 => "vite-staged",
-            82 => "lint-staged",
-            83 => "nano-staged",
-            84 => "pre-commit",
-            85 => "commitlint" => transform_value(value, sort_object_recursive),
-            // VSCode Extension Specific
-            86 => "l10n",
-            87 => "contributes",
-            88 => "activationEvents" => transform_array(value, sort_array_unique),
-            89 => "extensionPack" => transform_array(value, sort_array_unique),
-            90 => "extensionDependencies" => transform_array(value, sort_array_unique),
-            91 => "extensionKind" => transform_array(value, sort_array_unique),
-            92 => "icon",
-            93 => "badges",
-            94 => "galleryBanner",
-            95 => "preview",
-            96 => "markdown",
-            // Build & Tool Configuration
-            97 => "napi" => transform_value(value, sort_object_alphabetically),
-            98 => "flat",
-            99 => "config" => transform_value(value, sort_object_alphabetically),
-            100 => "nodemonConfig" => transform_value(value, sort_object_recursive),
-            101 => "browserify" => transform_value(value, sort_object_recursive),
-            102 => "babel" => transform_value(value, sort_object_recursive),
-            103 => "browserslist",
-            104 => "xo" => transform_value(value, sort_object_recursive),
-            105 => "prettier" => transform_value(value, sort_object_recursive),
-            106 => "eslintConfig" => transform_value(value, sort_object_recursive),
-            107 => "eslintIgnore",
-            108 => "standard" => transform_value(value, sort_object_recursive),
-            109 => "npmpkgjsonlint",
-            110 => "npmPackageJsonLintConfig",
-            111 => "npmpackagejsonlint",
-            112 => "release",
-            // Only sorts top-level keys: `replaceText` maps regexes to replacements applied sequentially in key order
-            113 => "auto-changelog" => transform_value(value, sort_object_alphabetically),
-            // Only sorts top-level keys: `plugins` in object form runs plugins in key order
-            114 => "remarkConfig" => transform_value(value, sort_object_alphabetically),
-            115 => "stylelint" => transform_value(value, sort_object_recursive),
-            116 => "typescript" => transform_value(value, sort_object_recursive),
-            117 => "typedoc" => transform_value(value, sort_object_recursive),
-            // Only sorts top-level keys: `exports` values may be pass-through conditional exports
-            118 => "tshy" => transform_value(value, sort_object_alphabetically),
-            119 => "tsdown" => transform_value(value, sort_object_recursive),
-            120 => "size-limit",
-            // Testing
-            121 => "ava" => transform_value(value, sort_object_recursive),
-            // Only sorts top-level keys: nested config like `moduleNameMapper` is order-dependent
-            122 => "jest" => transform_value(value, sort_object_alphabetically),
-            123 => "jest-junit",
-            124 => "jest-stare",
-            125 => "mocha" => transform_value(value, sort_object_recursive),
-            126 => "nyc" => transform_value(value, sort_object_recursive),
-            127 => "c8" => transform_value(value, sort_object_recursive),
-            128 => "tap",
-            129 => "tsd" => transform_value(value, sort_object_recursive),
-            130 => "typeCoverage" => transform_value(value, sort_object_recursive),
-            131 => "oclif" => transform_value(value, sort_object_recursive),
-            // Runtime & Package Manager
-            132 => "languageName",
-            133 => "preferGlobal",
-            134 => "devEngines" => transform_value(value, sort_object_alphabetically),
-            135 => "engines" => transform_value(value, sort_object_alphabetically),
-            136 => "engineStrict",
-            137 => "volta" => transform_value(value, sort_object_recursive),
-            138 => "packageManager",
-            139 => "pnpm",
-        ]);
+    for (position, (key, value)) in obj.into_iter().enumerate() {
+        if let Some(rule) = rules.iter().find(|rule| rule.applies(&key)) {
+            let value = if options.top_level_only { value } else { rule.transform(value) };
+            if is_removable_empty(&key, &value, options) {
+                continue;
+            }
+            match (custom_index(&key), rule.order()) {
+                (Some(idx), _) => known.push((idx, key, value)),
+                (None, Some(idx)) if !alphabetical_only => {
+                    ruled.push((custom.len() + idx, key, value));
+                }
+                _ => push_unknown(position, key, value),
+            }
+            continue;
+        }
+        let (value, overridden) = match options.field_strategies.get(&key) {
+            Some(strategy) if !options.top_level_only => (strategy.apply(value, options), true),
+            _ => (value, false),
+        };
+        let transform = !overridden && !options.top_level_only;
+        let field = known_field(&key, value, options, rules, transform);
+        let (Ok((_, value)) | Err(value)) = &field;
+        if is_removable_empty(&key, value, options) {
+            continue;
+        }
+        match (custom_index(&key), field) {
+            (Some(idx), Ok((_, value)) | Err(value)) => known.push((idx, key, value)),
+            (None, Ok((idx, value))) if !alphabetical_only => {
+                let idx = custom.len() + dependency_field_index(&key, idx, options);
+                known.push((idx, key, value));
+            }
+            (None, Ok((_, value)) | Err(value)) => push_unknown(position, key, value),
+        }
     }
 
-    known.sort_unstable_by_key(|(idx, _, _)| *idx);
-    // Single sort over all unknowns: non-private (`!_`) before private (`_`-prefixed),
-    // each group alphabetical.
-    unknown.sort_unstable_by(|(a, _), (b, _)| {
+    // Built-in indices are unique, so the stable sort only matters for ruled fields: they
+    // land after the built-in field sharing their index.
+    known.append(&mut ruled);
+    known.sort_by_key(|(idx, _, _)| *idx);
+    // Single sort over all unknowns: non-private (`!_`) before private (`_`-prefixed) unless
+    // they are merged, each group alphabetical. The sort is stable, so skipping the
+    // alphabetical step (`sort_unknown_fields` off, or preserved private fields) keeps
+    // input order.
+    let placement = options.private_fields;
+    unknown.sort_by(|(a, _), (b, _)| {
         let a_priv = a.starts_with('_');
         let b_priv = b.starts_with('_');
-        a_priv.cmp(&b_priv).then_with(|| a.cmp(b))
+        let by_group = if placement == PrivateFieldPlacement::Merge {
+            Ordering::Equal
+        } else {
+            a_priv.cmp(&b_priv)
+        };
+        let alphabetical = (options.sort_unknown_fields || alphabetical_only)
+            && !(placement == PrivateFieldPlacement::Preserve && a_priv && b_priv);
+        if alphabetical { by_group.then_with(|| compare_keys(a, b, options)) } else { by_group }
     });
+    let private = if placement == PrivateFieldPlacement::Merge {
+        Vec::new()
+    } else {
+        let split = unknown.partition_point(|(key, _)| !key.starts_with('_'));
+        unknown.split_off(split)
+    };
 
-    let mut result = Map::with_capacity(known.len() + unknown.len());
-    for (_, key, value) in known {
-        result.insert(key, value);
-    }
-    for (key, value) in unknown {
-        result.insert(key, value);
+    let known = known.into_iter().map(|(_, key, value)| (key, value));
+    match placement {
+        PrivateFieldPlacement::First => private.into_iter().chain(known).chain(unknown).collect(),
+        PrivateFieldPlacement::Last | PrivateFieldPlacement::Merge => {
+            known.chain(unknown).chain(private).collect()
+        }
+        PrivateFieldPlacement::Preserve => {
+            let mut entries: Vec<(String, Value)> = known.chain(unknown).collect();
+            // Positions are ascending, so inserting in order lands each field back at its
+            // original index.
+            for (pos, entry) in private_positions.into_iter().zip(private) {
+                entries.insert(pos.min(entries.len()), entry);
+            }
+            entries.into_iter().collect()
+        }
     }
-    result
 }
@@ -0,0 +1,7 @@
+mod cli;
+
+use std::{env, process};
+
+fn main() {
+    process::exit(cli::run(env::args_os()).code());
+}
@@ -0,0 +1,91 @@
+//! Shorthand expansion for [`SortOptions::normalize`].
+
+use serde_json::{Map, Value};
+
+use crate::SortOptions;
+
+/// Expands a string `bugs` into `{ "url": ... }` (or `{ "email": ... }` for an address).
+pub(crate) fn bugs(value: Value, options: &SortOptions) -> Value {
+    match value {
+        Value::String(s) if options.normalize => {
+            let key = if !s.contains("://") && s.contains('@') { "email" } else { "url" };
+            Value::Object(Map::from_iter([(key.to_string(), Value::String(s))]))
+        }
+        other => other,
+    }
+}
+
+/// Parses a `"Name <email> (url)"` person string into `{ name, email, url }`, omitting
+/// missing parts.
+pub(crate) fn person(value: Value, options: &SortOptions) -> Value {
+    match value {
+        Value::String(s) if options.normalize => {
+            let mut obj = Map::new();
+            let name_end = s.find(['<', '(']).unwrap_or(s.len());
+            let name = s[..name_end].trim();
+            if !name.is_empty() {
+                obj.insert("name".into(), name.into());
+            }
+            for (key, open, close) in [("email", '<', '>'), ("url", '(', ')')] {
+                let part = s
+                    .find(open)
+                    .and_then(|start| {
+                        s[start + 1..].find(close).map(|len| &s[start + 1..start + 1 + len])
+                    })
+                    .map(str::trim)
+                    .filter(|part| !part.is_empty());
+                if let Some(part) = part {
+                    obj.insert(key.into(), part.into());
+                }
+            }
+            // A string with nothing parseable is left as-is rather than becoming `{}`.
+            if obj.is_empty() { Value::String(s) } else { Value::Object(obj) }
+        }
+        other => other,
+    }
+}
+
+/// Normalizes every string entry of a people array (`contributors`, `maintainers`).
+pub(crate) fn people(value: Value, options: &SortOptions) -> Value {
+    match value {
+        Value::Array(arr) if options.normalize => {
+            Value::Array(arr.into_iter().map(|person| self::person(person, options)).collect())
+        }
+        other => other,
+    }
+}
+
+/// Expands a `repository` string (`user/repo`, `github:user/repo`, a URL, ...) into
+/// `{ "type": "git", "url": ... }`, matching what npm writes.
+pub(crate) fn repository(value: Value, options: &SortOptions) -> Value {
+    match value {
+        Value::String(s) if options.normalize => {
+            let url = expand_repository_shorthand(&s).unwrap_or(s);
+            Value::Object(Map::from_iter([
+                ("type".to_string(), Value::from("git")),
+                ("url".to_string(), Value::String(url)),
+            ]))
+        }
+        other => other,
+    }
+}
+
+fn expand_repository_shorthand(s: &str) -> Option<String> {
+    const HOSTS: &[(&str, &str)] = &[
+        ("github:", "github.com"),
+        ("gitlab:", "gitlab.com"),
+        ("bitbucket:", "bitbucket.org"),
+        ("gist:", "gist.github.com"),
+    ];
+    let (host, path) = HOSTS
+        .iter()
+        .find_map(|(prefix, host)| s.strip_prefix(prefix).map(|path| (*host, path)))
+        .or_else(|| {
+            // Bare `user/repo` means GitHub.
+            let is_bare =
+                !s.contains(':') && s.split('/').filter(|part| !part.is_empty()).count() == 2;
+            is_bare.then_some(("github.com", s))
+        })?;
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    Some(format!("git+https://{host}/{path}.git"))
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    Diagnostic, SortError, SortOptions, parse_package_json, sort_object_keys, values_identical,
+};
+
+/// A sorted document together with a summary of what sorting changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortReport {
+    /// The sorted document.
+    pub output: String,
+    /// Top-level keys that had to move to reach the sorted order, in output order. Keys that
+    /// kept their relative position are not listed, so moving one field to the top reports
+    /// only that field.
+    pub reordered_keys: Vec<String>,
+    /// Top-level fields whose value was rewritten by a nested transform (e.g. sorted
+    /// `dependencies` or deduplicated `keywords`).
+    pub transformed_fields: Vec<String>,
+    /// Entries removed from top-level arrays, such as duplicate `keywords`.
+    pub removed_entries: Vec<RemovedEntry>,
+    /// Non-fatal problems noticed in the input, such as duplicate keys, non-string entries
+    /// in string arrays, or well-known fields with an unexpected type.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// An array entry dropped while sorting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemovedEntry {
+    /// The top-level field the array belongs to.
+    pub field: String,
+    /// The removed value.
+    pub value: Value,
+}
+
+/// Sorts a `package.json` string and describes which keys moved, which fields were
+/// transformed, which array entries were removed, and any [`Diagnostic`]s.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`](crate::sort_package_json_with_options).
+pub fn sort_package_json_with_report(
+    input: &str,
+    options: &SortOptions,
+) -> Result<SortReport, SortError> {
+    let (format, original) = parse_package_json(input, options)?;
+    let sorted = sort_object_keys(original.clone(), options);
+
+    let reordered_keys = reordered_keys(&original, &sorted);
+    let diagnostics = crate::diagnostics::collect(
+        input.trim_start_matches(crate::BOM_STR),
+        &original,
+        options.lenient,
+    );
+    let mut transformed_fields = Vec::new();
+    let mut removed_entries = Vec::new();
+    for (key, value) in &sorted {
+        let Some(before) = original.get(key) else { continue };
+        if values_identical(before, value) {
+            continue;
+        }
+        transformed_fields.push(key.clone());
+        if let (Value::Array(before), Value::Array(after)) = (before, value) {
+            removed_entries.extend(
+                removed_values(before, after)
+                    .map(|value| RemovedEntry { field: key.clone(), value: value.clone() }),
+            );
+        }
+    }
+
+    let output = crate::write_package_json(&sorted, format, options)?;
+    Ok(SortReport { output, reordered_keys, transformed_fields, removed_entries, diagnostics })
+}
+
+/// Returns the keys of `sorted` outside the longest run that already appears in the same
+/// relative order in `original` — the minimal set of keys that had to move.
+fn reordered_keys(original: &Map<String, Value>, sorted: &Map<String, Value>) -> Vec<String> {
+    // Original index of each key, in sorted order.
+    let index: HashMap<&str, usize> =
+        original.keys().enumerate().map(|(i, key)| (key.as_str(), i)).collect();
+    let positions: Vec<usize> = sorted.keys().map(|key| index[key.as_str()]).collect();
+
+    // Longest increasing subsequence (patience sorting) over `positions`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; positions.len()];
+    for (i, &pos) in positions.iter().enumerate() {
+        let slot = tails.partition_point(|&t| positions[t] < pos);
+        if slot > 0 {
+            prev[i] = Some(tails[slot - 1]);
+        }
+        if slot == tails.len() {
+            tails.push(i);
+        } else {
+            tails[slot] = i;
+        }
+    }
+    let mut in_place = vec![false; positions.len()];
+    let mut cursor = tails.last().copied();
+    while let Some(i) = cursor {
+        in_place[i] = true;
+        cursor = prev[i];
+    }
+
+    sorted.keys().zip(in_place).filter(|(_, kept)| !kept).map(|(key, _)| key.clone()).collect()
+}
+
+/// Yields each value of `before` that no longer appears in `after`, counting multiplicity
+/// so that a duplicate is reported once per dropped copy.
+fn removed_values<'a>(before: &'a [Value], after: &'a [Value]) -> impl Iterator<Item = &'a Value> {
+    before
+        .iter()
+        .enumerate()
+        .filter(move |&(i, value)| {
+            let seen = before[..i].iter().filter(|v| *v == value).count();
+            let kept = after.iter().filter(|v| *v == value).count();
+            seen >= kept
+        })
+        .map(|(_, value)| value)
+}
@@ -0,0 +1,62 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    InputFormat, SortError, SortOptions, Visitor, parse_package_json, sort_object_keys, visitor,
+};
+
+/// A parsed and sorted `package.json`, for callers that want to inspect or tweak fields
+/// (e.g. bump `version`) before serializing.
+#[derive(Debug, Clone)]
+pub struct SortedPackageJson {
+    obj: Map<String, Value>,
+    format: InputFormat,
+}
+
+impl SortedPackageJson {
+    /// Parses and sorts a `package.json` string.
+    ///
+    /// # Errors
+    ///
+    /// See [`sort_package_json_with_options`](crate::sort_package_json_with_options).
+    pub fn parse(input: &str, options: &SortOptions) -> Result<Self, SortError> {
+        let (format, obj) = parse_package_json(input, options)?;
+        Ok(Self { obj: sort_object_keys(obj, options), format })
+    }
+
+    /// The sorted top-level object.
+    pub fn as_map(&self) -> &Map<String, Value> {
+        &self.obj
+    }
+
+    /// Returns the value of a top-level field.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.obj.get(key)
+    }
+
+    /// Returns a mutable reference to a top-level field's value. Editing a value in place
+    /// keeps the field's position.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.obj.get_mut(key)
+    }
+
+    /// Runs `visitor` over the top-level fields.
+    pub fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+        visitor::walk(&mut self.obj, visitor);
+    }
+
+    /// Consumes the document, returning the sorted top-level object.
+    pub fn into_map(self) -> Map<String, Value> {
+        self.obj
+    }
+
+    /// Serializes the document. The input's BOM is kept, and
+    /// [`TrailingNewline::Preserve`](crate::TrailingNewline::Preserve) refers to the input
+    /// given to [`parse`](Self::parse).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SortError::Io`] if serialization fails.
+    pub fn to_string(&self, options: &SortOptions) -> Result<String, SortError> {
+        crate::write_package_json(&self.obj, self.format, options)
+    }
+}
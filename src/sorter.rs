@@ -0,0 +1,77 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::{SortError, SortOptions, parse_package_json, sort_object_keys_with_rules};
+
+/// A custom rule for top-level fields the built-in field table doesn't know about (or
+/// should handle differently), registered on a [`Sorter`].
+pub trait FieldRule {
+    /// Whether this rule handles the field named `key`.
+    fn applies(&self, key: &str) -> bool;
+
+    /// Rewrites the field's value. The built-in transform and any
+    /// [`FieldStrategy`](crate::FieldStrategy) for the field are skipped.
+    fn transform(&self, value: Value) -> Value {
+        value
+    }
+
+    /// The field's index in [`default_field_order`](crate::default_field_order). A field
+    /// sharing an index with a built-in field goes right after it. `None` sorts the field
+    /// with the unknown fields.
+    fn order(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Sorts `package.json` documents with a fixed set of options and extra [`FieldRule`]s.
+pub struct Sorter {
+    options: SortOptions,
+    rules: Vec<Box<dyn FieldRule>>,
+}
+
+impl Sorter {
+    /// Creates a sorter with no extra rules.
+    pub fn new(options: SortOptions) -> Self {
+        Self { options, rules: Vec::new() }
+    }
+
+    /// Registers a rule. Rules are consulted in registration order, before the built-in
+    /// field table; the first one that applies to a field wins.
+    #[must_use]
+    pub fn with_rule<R: FieldRule + 'static>(mut self, rule: R) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// The options this sorter was created with.
+    pub fn options(&self) -> &SortOptions {
+        &self.options
+    }
+
+    /// Sorts a `package.json` string.
+    ///
+    /// # Errors
+    ///
+    /// See [`sort_package_json_with_options`](crate::sort_package_json_with_options).
+    pub fn sort(&self, input: &str) -> Result<String, SortError> {
+        let (format, obj) = parse_package_json(input, &self.options)?;
+        let sorted = sort_object_keys_with_rules(obj, &self.options, &self.rules);
+        crate::write_package_json(&sorted, format, &self.options)
+    }
+}
+
+impl Default for Sorter {
+    fn default() -> Self {
+        Self::new(SortOptions::default())
+    }
+}
+
+impl fmt::Debug for Sorter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sorter")
+            .field("options", &self.options)
+            .field("rules", &self.rules.len())
+            .finish()
+    }
+}
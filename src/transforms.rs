@@ -0,0 +1,389 @@
+//! The building blocks the built-in field table applies to values, for manifest tooling
+//! that wants to sort parts of a document the same way.
+//!
+//! Each function takes the value by ownership and returns it sorted. Comparisons honor the
+//! relevant [`SortOptions`] (`comparator`, `natural_sort`, `dedupe_arrays`, ...).
+//!
+//! These functions do not validate the options. Without the `collation` feature,
+//! `unicode_collation` is ignored here and code point (or natural) order is used instead,
+//! whereas the document-level functions reject it with [`SortError::Config`].
+//!
+//! [`SortError::Config`]: crate::SortError::Config
+
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+use crate::{ExportsMode, SortOptions, compare};
+
+/// Compares two keys (or array strings) in an alphabetical section, honoring
+/// [`SortOptions::comparator`], `unicode_collation`, and [`SortOptions::natural_sort`], in
+/// that order of precedence.
+pub(crate) fn compare_keys(a: &str, b: &str, options: &SortOptions) -> Ordering {
+    if let Some(comparator) = &options.comparator {
+        return comparator.compare(a, b);
+    }
+    #[cfg(feature = "collation")]
+    if options.unicode_collation {
+        return compare::unicode_cmp(a, b);
+    }
+    if options.natural_sort { compare::natural_cmp(a, b) } else { a.cmp(b) }
+}
+
+/// Compares `/`-separated paths segment by segment, so a path sorts right after its parent
+/// (`pkg`, `pkg/**/left-pad`, `pkg-a`). Segments compare like [`compare_keys`] with
+/// [`SortOptions::natural_sort`] forced on.
+pub(crate) fn compare_paths(a: &str, b: &str, options: &SortOptions) -> Ordering {
+    compare_paths_by(a, b, |a, b| compare_segments(a, b, options))
+        // A `comparator` may call distinct segments equal; keep the result total.
+        .then_with(|| compare_keys(a, b, options))
+        .then_with(|| a.cmp(b))
+}
+
+fn compare_paths_by(
+    a: &str,
+    b: &str,
+    compare_segments: impl Fn(&str, &str) -> Ordering,
+) -> Ordering {
+    let (mut a_segments, mut b_segments) = (a.split('/'), b.split('/'));
+    loop {
+        let ordering = match (a_segments.next(), b_segments.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(a_segment), Some(b_segment)) => compare_segments(a_segment, b_segment),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn compare_segments(a: &str, b: &str, options: &SortOptions) -> Ordering {
+    if options.comparator.is_some() {
+        return compare_keys(a, b, options);
+    }
+    #[cfg(feature = "collation")]
+    if options.unicode_collation {
+        return compare::unicode_cmp(a, b);
+    }
+    compare::natural_cmp(a, b)
+}
+
+/// Whether [`compare_keys`] is plain code point order, which `Map::sort_keys` implements.
+fn is_code_point_order(options: &SortOptions) -> bool {
+    #[cfg(feature = "collation")]
+    if options.unicode_collation {
+        return false;
+    }
+    options.comparator.is_none() && !options.natural_sort
+}
+
+fn sort_keys_in_place(obj: &mut Map<String, Value>, options: &SortOptions) {
+    if is_code_point_order(options) {
+        obj.sort_keys();
+        return;
+    }
+    let mut entries: Vec<(String, Value)> = std::mem::take(obj).into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| compare_keys(a, b, options));
+    *obj = entries.into_iter().collect();
+}
+
+/// Sorts an object's keys alphabetically (per [`SortOptions::comparator`],
+/// `unicode_collation`, and [`SortOptions::natural_sort`]), leaving nested values as they are.
+pub fn sort_object_alphabetically(
+    mut obj: Map<String, Value>,
+    options: &SortOptions,
+) -> Map<String, Value> {
+    sort_keys_in_place(&mut obj, options);
+    obj
+}
+
+/// Sorts an object's keys alphabetically at every level of nesting. Arrays keep their order.
+pub fn sort_object_recursive(
+    mut obj: Map<String, Value>,
+    options: &SortOptions,
+) -> Map<String, Value> {
+    sort_object_recursive_in_place(&mut obj, options);
+    obj
+}
+
+fn sort_object_recursive_in_place(obj: &mut Map<String, Value>, options: &SortOptions) {
+    for value in obj.values_mut() {
+        if let Value::Object(nested) = value {
+            sort_object_recursive_in_place(nested, options);
+        }
+    }
+    sort_keys_in_place(obj, options);
+}
+
+/// Sorts a dependency map by package name, exactly as `dependencies`, `devDependencies`,
+/// ... are sorted: ignoring case when [`SortOptions::case_insensitive_dependencies`] is set
+/// (and no `comparator` is).
+pub fn sort_dependencies(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    if !options.case_insensitive_dependencies || options.comparator.is_some() {
+        return sort_object_alphabetically(obj, options);
+    }
+    let mut entries: Vec<(String, Value)> = obj.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| compare_dependencies(a, b, options));
+    entries.into_iter().collect()
+}
+
+/// The package name order of [`sort_dependencies`].
+fn compare_dependencies(a: &str, b: &str, options: &SortOptions) -> Ordering {
+    if options.case_insensitive_dependencies && options.comparator.is_none() {
+        compare::case_insensitive_cmp(a, b)
+    } else {
+        compare_keys(a, b, options)
+    }
+}
+
+/// Sorts a `dependenciesMeta` map by package name (or `name@version` selector), as
+/// [`sort_dependencies`] sorts `dependencies`, and each entry's settings (`built`,
+/// `injected`, `optional`, `unplugged`, ...) alphabetically, as the yarn and pnpm docs
+/// list them.
+pub fn sort_dependencies_meta(
+    obj: Map<String, Value>,
+    options: &SortOptions,
+) -> Map<String, Value> {
+    let mut obj = sort_dependencies(obj, options);
+    for value in obj.values_mut() {
+        if let Value::Object(entry) = value {
+            sort_keys_in_place(entry, options);
+        }
+    }
+    obj
+}
+
+/// Sorts a `peerDependenciesMeta` map by package name, as [`sort_dependencies`] sorts
+/// `peerDependencies`, with `optional` first in each entry and any other keys following
+/// alphabetically.
+pub fn sort_peer_dependencies_meta(
+    obj: Map<String, Value>,
+    options: &SortOptions,
+) -> Map<String, Value> {
+    let mut obj = sort_dependencies(obj, options);
+    for value in obj.values_mut() {
+        if let Value::Object(entry) = value {
+            *entry = sort_object_by_key_order(std::mem::take(entry), options, &["optional"]);
+        }
+    }
+    obj
+}
+
+/// Sorts an npm `overrides` map by package name at every level of nesting, with the `"."`
+/// key (the version of the overridden package itself) first in each object, as npm's docs
+/// write it.
+pub fn sort_overrides(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    let mut obj = sort_object_by_key_order(obj, options, &["."]);
+    for value in obj.values_mut() {
+        if let Value::Object(nested) = value {
+            *nested = sort_overrides(std::mem::take(nested), options);
+        }
+    }
+    obj
+}
+
+/// Sorts string entries ascending and removes duplicates unless
+/// [`SortOptions::dedupe_arrays`] is off. Non-string entries are kept after the strings, in
+/// their original order, so sorting is never lossy.
+pub fn sort_array(arr: Vec<Value>, options: &SortOptions) -> Vec<Value> {
+    sort_strings_by(arr, options, compare_keys)
+}
+
+/// Sorts `/`-separated paths segment by segment in natural order, so `dist/2.js` comes
+/// before `dist/10.js` whatever [`SortOptions::natural_sort`] says and `pkg/**/left-pad`
+/// stays next to `pkg` rather than after `pkg-a`. A `comparator` or `unicode_collation`
+/// replaces natural order within segments. Removes duplicates unless
+/// [`SortOptions::dedupe_arrays`] is off; non-string entries are kept after the strings, in
+/// their original order. Suits path lists such as `workspaces` globs.
+pub fn sort_paths_naturally(arr: Vec<Value>, options: &SortOptions) -> Vec<Value> {
+    sort_strings_by(arr, options, compare_paths)
+}
+
+fn sort_strings_by(
+    arr: Vec<Value>,
+    options: &SortOptions,
+    compare: fn(&str, &str, &SortOptions) -> Ordering,
+) -> Vec<Value> {
+    let (mut strings, others): (Vec<Value>, Vec<Value>) =
+        arr.into_iter().partition(Value::is_string);
+    // `unwrap` is sound: `partition` above guarantees every element is a string.
+    strings.sort_by(|a, b| compare(a.as_str().unwrap(), b.as_str().unwrap(), options));
+    if options.dedupe_arrays {
+        strings.dedup_by(|a, b| a.as_str() == b.as_str());
+    }
+    strings.extend(others);
+    strings
+}
+
+/// Sorts an object's keys as [`sort_paths_naturally`] sorts paths, so `*.2.js` comes before
+/// `*.10.js` whatever [`SortOptions::natural_sort`] says and `src/**/*.ts` stays before
+/// `src-legacy/*.js`, leaving the values as they are. Suits glob-keyed tool configs such as
+/// `lint-staged`, whose command arrays run in order.
+pub fn sort_keys_naturally(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    let mut entries: Vec<(String, Value)> = obj.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| compare_paths(a, b, options));
+    entries.into_iter().collect()
+}
+
+/// Sorts a yarn `resolutions` map by selector, as [`sort_keys_naturally`] sorts keys, so
+/// `pkg/**/left-pad` stays next to `pkg` rather than after `pkg-a`. Selectors ignore case
+/// when [`SortOptions::case_insensitive_dependencies`] is set (and no `comparator` is), as
+/// [`sort_dependencies`] does.
+pub fn sort_resolutions(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    if !options.case_insensitive_dependencies || options.comparator.is_some() {
+        return sort_keys_naturally(obj, options);
+    }
+    let mut entries: Vec<(String, Value)> = obj.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| compare_resolutions(a, b, options));
+    entries.into_iter().collect()
+}
+
+/// The selector order of [`sort_resolutions`].
+fn compare_resolutions(a: &str, b: &str, options: &SortOptions) -> Ordering {
+    if options.case_insensitive_dependencies && options.comparator.is_none() {
+        compare_paths_by(a, b, compare::natural_case_insensitive_cmp).then_with(|| a.cmp(b))
+    } else {
+        compare_paths(a, b, options)
+    }
+}
+
+/// Removes duplicate string entries while preserving original order; non-string entries
+/// are kept. Used for fields where order matters (e.g., `files` with `!` negation
+/// patterns). A no-op when [`SortOptions::dedupe_arrays`] is off.
+pub fn dedupe_array(mut arr: Vec<Value>, options: &SortOptions) -> Vec<Value> {
+    if !options.dedupe_arrays {
+        return arr;
+    }
+    let mut write = 0;
+    for read in 0..arr.len() {
+        let keep = match arr[read].as_str() {
+            Some(s) => !arr[..write].iter().any(|seen| seen.as_str() == Some(s)),
+            None => true,
+        };
+        if keep {
+            if write != read {
+                arr.swap(write, read);
+            }
+            write += 1;
+        }
+    }
+    arr.truncate(write);
+    arr
+}
+
+/// Reorders `obj` so that any keys present in `key_order` appear first (in the given
+/// order), with the remaining keys following alphabetically.
+///
+/// Single-pass classification + merge — avoids `IndexMap::shift_remove`'s O(n) tail-shift
+/// per requested key.
+pub fn sort_object_by_key_order(
+    obj: Map<String, Value>,
+    options: &SortOptions,
+    key_order: &[&str],
+) -> Map<String, Value> {
+    let mut known: Vec<Option<(String, Value)>> = (0..key_order.len()).map(|_| None).collect();
+    let mut others: Vec<(String, Value)> = Vec::new();
+
+    for (key, value) in obj {
+        match key_order.iter().position(|kn| *kn == key.as_str()) {
+            Some(idx) => known[idx] = Some((key, value)),
+            None => others.push((key, value)),
+        }
+    }
+
+    others.sort_by(|(a, _), (b, _)| compare_keys(a, b, options));
+
+    let mut result = Map::with_capacity(known.len() + others.len());
+    for (key, value) in known.into_iter().flatten() {
+        result.insert(key, value);
+    }
+    for (key, value) in others {
+        result.insert(key, value);
+    }
+    result
+}
+
+/// Sorts a `scripts` map alphabetically, as the `scripts` field is sorted when
+/// [`SortOptions::sort_scripts`] is on.
+pub fn sort_scripts(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    sort_object_alphabetically(obj, options)
+}
+
+/// Git hooks in the order `githooks(5)` documents them, which follows the order git runs
+/// them in.
+const GIT_HOOKS: &[&str] = &[
+    "applypatch-msg",
+    "pre-applypatch",
+    "post-applypatch",
+    "pre-commit",
+    "pre-merge-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+    "pre-receive",
+    "update",
+    "proc-receive",
+    "post-receive",
+    "post-update",
+    "reference-transaction",
+    "push-to-checkout",
+    "pre-auto-gc",
+    "post-rewrite",
+    "sendemail-validate",
+    "fsmonitor-watchman",
+    "p4-changelist",
+    "p4-prepare-changelist",
+    "p4-post-changelist",
+    "p4-pre-submit",
+    "post-index-change",
+];
+
+/// Orders git hook names (as in `simple-git-hooks`) by when git runs them: `pre-commit`,
+/// `prepare-commit-msg`, `commit-msg`, `post-commit`, `pre-push`, ... Other keys follow
+/// alphabetically.
+pub fn sort_git_hooks(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    sort_object_by_key_order(obj, options, GIT_HOOKS)
+}
+
+/// Sorts a `husky` config's keys alphabetically at every level, except that its `hooks` are
+/// ordered by [`sort_git_hooks`].
+pub fn sort_husky(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    let mut obj = sort_object_recursive(obj, options);
+    if let Some(Value::Object(hooks)) = obj.get_mut("hooks") {
+        *hooks = sort_git_hooks(std::mem::take(hooks), options);
+    }
+    obj
+}
+
+/// Sorts an `exports` or `imports` object per [`SortOptions::exports_mode`], exactly as
+/// those fields are sorted.
+pub fn sort_exports(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    match options.exports_mode {
+        ExportsMode::Preserve => obj,
+        ExportsMode::GroupPaths => sort_subpaths(obj, options),
+        ExportsMode::Off => sort_object_recursive(obj, options),
+    }
+}
+
+/// Sorts an `exports` or `imports` object's subpath keys with `"."` first. Objects holding
+/// conditions (any key not starting with `.` or `#`) are left alone: their order is
+/// resolution order.
+pub fn sort_subpaths(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    if obj.keys().all(|key| key.starts_with(['.', '#'])) {
+        sort_object_by_key_order(obj, options, &["."])
+    } else {
+        obj
+    }
+}
+
+/// Orders a person object (`author`, `contributors` entries, ...) as `name`, `email`, `url`,
+/// then any other keys alphabetically.
+pub fn sort_people_object(obj: Map<String, Value>, options: &SortOptions) -> Map<String, Value> {
+    sort_object_by_key_order(obj, options, &["name", "email", "url"])
+}
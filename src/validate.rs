@@ -0,0 +1,260 @@
+//! A built-in subset of the [SchemaStore `package.json` schema](https://json.schemastore.org/package.json),
+//! checked against the parsed document.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::{SortError, SortOptions, parse_package_json, sort_object_keys};
+
+/// A schema violation. `path` is a JSON Pointer to the offending value (e.g.
+/// `/author/name`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON Pointer to the offending value.
+    pub path: String,
+    /// What the schema expects.
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// A sorted document together with the schema violations found in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedPackageJson {
+    /// The sorted document.
+    pub output: String,
+    /// Schema violations found in the sorted document. Empty when it is valid.
+    pub errors: Vec<ValidationError>,
+}
+
+/// Sorts a `package.json` string and validates it against a built-in subset of the
+/// SchemaStore schema. Validation never prevents sorting.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`](crate::sort_package_json_with_options).
+pub fn sort_package_json_with_validation(
+    input: &str,
+    options: &SortOptions,
+) -> Result<ValidatedPackageJson, SortError> {
+    let (format, obj) = parse_package_json(input, options)?;
+    let sorted = sort_object_keys(obj, options);
+    let errors = validate(&sorted);
+    let output = crate::write_package_json(&sorted, format, options)?;
+    Ok(ValidatedPackageJson { output, errors })
+}
+
+/// The shape a field's value must have.
+#[derive(Clone, Copy)]
+enum Shape {
+    String,
+    Boolean,
+    Object,
+    /// An array of strings.
+    StringArray,
+    /// An object whose values are all strings (`scripts`, `dependencies`, ...).
+    StringMap,
+    /// A string or an object whose values are all strings (`bin`).
+    StringOrStringMap,
+    /// `"module"` or `"commonjs"`.
+    ModuleType,
+    /// A string, or an object with string `url`/`email` (`bugs`).
+    Bugs,
+    /// A string, or an object with a required `name` (`author`).
+    Person,
+    /// An array of [`Shape::Person`] (`contributors`, `maintainers`).
+    People,
+    /// A string, or an object with string `type`/`url` (`repository`).
+    Repository,
+    /// An array of strings, or an object with a `packages` array (`workspaces`).
+    Workspaces,
+}
+
+const SCHEMA: &[(&str, Shape)] = &[
+    ("name", Shape::String),
+    ("version", Shape::String),
+    ("description", Shape::String),
+    ("keywords", Shape::StringArray),
+    ("homepage", Shape::String),
+    ("bugs", Shape::Bugs),
+    ("license", Shape::String),
+    ("author", Shape::Person),
+    ("contributors", Shape::People),
+    ("maintainers", Shape::People),
+    ("files", Shape::StringArray),
+    ("main", Shape::String),
+    ("bin", Shape::StringOrStringMap),
+    ("types", Shape::String),
+    ("typings", Shape::String),
+    ("type", Shape::ModuleType),
+    ("repository", Shape::Repository),
+    ("scripts", Shape::StringMap),
+    ("dependencies", Shape::StringMap),
+    ("devDependencies", Shape::StringMap),
+    ("peerDependencies", Shape::StringMap),
+    ("optionalDependencies", Shape::StringMap),
+    ("engines", Shape::StringMap),
+    ("os", Shape::StringArray),
+    ("cpu", Shape::StringArray),
+    ("private", Shape::Boolean),
+    ("publishConfig", Shape::Object),
+    ("workspaces", Shape::Workspaces),
+    ("packageManager", Shape::String),
+];
+
+fn validate(obj: &Map<String, Value>) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for (key, value) in obj {
+        let Some(&(_, shape)) = SCHEMA.iter().find(|(field, _)| field == key) else { continue };
+        check(&format!("/{}", escape_pointer(key)), value, shape, &mut errors);
+    }
+    if let Some(Value::String(name)) = obj.get("name") {
+        if let Err(message) = check_name(name) {
+            errors.push(ValidationError { path: "/name".to_string(), message });
+        }
+    }
+    if let Some(Value::String(version)) = obj.get("version") {
+        if !is_semver(version) {
+            let message = format!("must be a valid semver version, found {version:?}");
+            errors.push(ValidationError { path: "/version".to_string(), message });
+        }
+    }
+    errors
+}
+
+fn check(path: &str, value: &Value, shape: Shape, errors: &mut Vec<ValidationError>) {
+    let mut fail = |path: String, message: &str| {
+        errors.push(ValidationError { path, message: message.to_string() });
+    };
+    match (shape, value) {
+        (Shape::String, Value::String(_))
+        | (Shape::Boolean, Value::Bool(_))
+        | (Shape::Object, Value::Object(_))
+        | (
+            Shape::StringOrStringMap | Shape::Bugs | Shape::Person | Shape::Repository,
+            Value::String(_),
+        ) => {}
+        (Shape::String, _) => fail(path.to_string(), "must be a string"),
+        (Shape::Boolean, _) => fail(path.to_string(), "must be a boolean"),
+        (Shape::Object, _) => fail(path.to_string(), "must be an object"),
+        (Shape::StringArray, Value::Array(items)) => {
+            for (i, item) in items.iter().enumerate() {
+                if !item.is_string() {
+                    fail(format!("{path}/{i}"), "must be a string");
+                }
+            }
+        }
+        (Shape::StringArray, _) => fail(path.to_string(), "must be an array of strings"),
+        (Shape::StringMap | Shape::StringOrStringMap, Value::Object(map)) => {
+            for (key, item) in map {
+                if !item.is_string() {
+                    fail(format!("{path}/{}", escape_pointer(key)), "must be a string");
+                }
+            }
+        }
+        (Shape::StringMap, _) => fail(path.to_string(), "must be an object of strings"),
+        (Shape::StringOrStringMap, _) => {
+            fail(path.to_string(), "must be a string or an object of strings");
+        }
+        (Shape::ModuleType, Value::String(ty)) if ty == "module" || ty == "commonjs" => {}
+        (Shape::ModuleType, _) => fail(path.to_string(), "must be \"module\" or \"commonjs\""),
+        (Shape::Bugs, Value::Object(map)) => {
+            check_string_members(path, map, &["url", "email"], errors)
+        }
+        (Shape::Bugs, _) => fail(path.to_string(), "must be a string or an object"),
+        (Shape::Person, Value::Object(map)) => {
+            if !map.contains_key("name") {
+                fail(path.to_string(), "must have a `name`");
+            }
+            check_string_members(path, map, &["name", "email", "url"], errors);
+        }
+        (Shape::Person, _) => fail(path.to_string(), "must be a string or an object"),
+        (Shape::People, Value::Array(items)) => {
+            for (i, item) in items.iter().enumerate() {
+                check(&format!("{path}/{i}"), item, Shape::Person, errors);
+            }
+        }
+        (Shape::People, _) => fail(path.to_string(), "must be an array"),
+        (Shape::Repository, Value::Object(map)) => {
+            check_string_members(path, map, &["type", "url", "directory"], errors);
+        }
+        (Shape::Repository, _) => fail(path.to_string(), "must be a string or an object"),
+        (Shape::Workspaces, Value::Array(_)) => check(path, value, Shape::StringArray, errors),
+        (Shape::Workspaces, Value::Object(map)) => {
+            if let Some(packages) = map.get("packages") {
+                check(&format!("{path}/packages"), packages, Shape::StringArray, errors);
+            }
+        }
+        (Shape::Workspaces, _) => fail(path.to_string(), "must be an array or an object"),
+    }
+}
+
+/// Checks that each of `members` present in `map` is a string.
+fn check_string_members(
+    path: &str,
+    map: &Map<String, Value>,
+    members: &[&str],
+    errors: &mut Vec<ValidationError>,
+) {
+    for &member in members {
+        if map.get(member).is_some_and(|value| !value.is_string()) {
+            let path = format!("{path}/{member}");
+            errors.push(ValidationError { path, message: "must be a string".to_string() });
+        }
+    }
+}
+
+/// Escapes a key for use in a JSON Pointer (RFC 6901).
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Checks npm's package name rules: at most 214 characters, lowercase, URL-safe, not
+/// starting with `.` or `_`, with an optional `@scope/` prefix.
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > 214 {
+        return Err("must be between 1 and 214 characters".to_string());
+    }
+    let bare = match name.strip_prefix('@') {
+        Some(scoped) => match scoped.split_once('/') {
+            Some((scope, bare)) if !scope.is_empty() && is_name_part(scope) => bare,
+            _ => return Err("must be of the form `@scope/name`".to_string()),
+        },
+        None => name,
+    };
+    if bare.is_empty() || bare.starts_with(['.', '_']) || !is_name_part(bare) {
+        return Err(format!(
+            "must be lowercase and URL-safe, not starting with `.` or `_`, found {name:?}"
+        ));
+    }
+    Ok(())
+}
+
+fn is_name_part(part: &str) -> bool {
+    part.bytes().all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~'))
+}
+
+/// Checks `MAJOR.MINOR.PATCH` with optional `-prerelease` and `+build` suffixes.
+fn is_semver(version: &str) -> bool {
+    let version = version
+        .split_once('+')
+        .map_or(version, |(version, build)| if build.is_empty() { "" } else { version });
+    let core = version.split_once('-').map_or(
+        version,
+        |(core, pre)| {
+            if pre.is_empty() { "" } else { core }
+        },
+    );
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part.bytes().all(|b| b.is_ascii_digit())
+                && (*part == "0" || !part.starts_with('0'))
+        })
+}
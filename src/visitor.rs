@@ -0,0 +1,66 @@
+use serde_json::{Map, Value};
+
+use crate::{SortError, SortOptions, SortedPackageJson};
+
+/// Observes or rewrites a sorted document field by field, with hooks for fields that have
+/// known semantics. Every hook defaults to doing nothing.
+///
+/// For each top-level field, [`visit_field`](Self::visit_field) runs first, followed by the
+/// semantic hook matching the field, if any. Rewritten values are written as-is; they are
+/// not sorted again.
+pub trait Visitor {
+    /// Called for every top-level field.
+    fn visit_field(&mut self, _key: &str, _value: &mut Value) {}
+
+    /// Called with the name-to-version map of `dependencies`, `devDependencies`,
+    /// `peerDependencies`, and `optionalDependencies`.
+    fn visit_dependencies(&mut self, _field: &str, _dependencies: &mut Map<String, Value>) {}
+
+    /// Called for `author` and for each entry of `contributors` and `maintainers`, either a
+    /// `"Name <email> (url)"` string or an object.
+    fn visit_person(&mut self, _field: &str, _person: &mut Value) {}
+
+    /// Called with the `scripts` object.
+    fn visit_scripts(&mut self, _scripts: &mut Map<String, Value>) {}
+
+    /// Called with the whole `exports` or `imports` tree.
+    fn visit_exports(&mut self, _field: &str, _exports: &mut Value) {}
+}
+
+/// Runs `visitor` over every top-level field of `obj`.
+pub(crate) fn walk<V: Visitor>(obj: &mut Map<String, Value>, visitor: &mut V) {
+    for (key, value) in obj.iter_mut() {
+        visitor.visit_field(key, value);
+        match (key.as_str(), value) {
+            (
+                "dependencies" | "devDependencies" | "peerDependencies" | "optionalDependencies",
+                Value::Object(dependencies),
+            ) => visitor.visit_dependencies(key, dependencies),
+            ("author", person) => visitor.visit_person(key, person),
+            ("contributors" | "maintainers", Value::Array(people)) => {
+                for person in people {
+                    visitor.visit_person(key, person);
+                }
+            }
+            ("scripts", Value::Object(scripts)) => visitor.visit_scripts(scripts),
+            ("exports" | "imports", exports) => visitor.visit_exports(key, exports),
+            _ => {}
+        }
+    }
+}
+
+/// Sorts a `package.json` string, running `visitor` over the sorted fields before
+/// serializing.
+///
+/// # Errors
+///
+/// See [`sort_package_json_with_options`](crate::sort_package_json_with_options).
+pub fn sort_package_json_with_visitor<V: Visitor>(
+    input: &str,
+    options: &SortOptions,
+    visitor: &mut V,
+) -> Result<String, SortError> {
+    let mut pkg = SortedPackageJson::parse(input, options)?;
+    pkg.visit(visitor);
+    pkg.to_string(options)
+}
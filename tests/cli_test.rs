@@ -0,0 +1,821 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+use serde_json::{Value, json};
+use sort_package_json::SortOptions;
+
+/// An empty directory for one test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("sort-package-json-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sort-package-json"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to run sort-package-json")
+}
+
+fn sort_package_json(dir: &Path, args: &[&str]) -> Option<i32> {
+    run(dir, args).status.code()
+}
+
+/// Runs with `input` on stdin.
+fn run_with_stdin(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sort-package-json"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run sort-package-json");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+const UNSORTED: &str = r#"{"version":"1.0.0","name":"a"}"#;
+const SORTED: &str = "{\n  \"name\": \"a\",\n  \"version\": \"1.0.0\"\n}\n";
+
+/// Writes an unsorted package.json into each of `dirs` under `dir`.
+fn write_unsorted(dir: &Path, dirs: &[&str]) {
+    for name in dirs {
+        fs::create_dir_all(dir.join(name)).unwrap();
+        fs::write(dir.join(name).join("package.json"), UNSORTED).unwrap();
+    }
+}
+
+/// The files `list` prints for `args`, sorted.
+fn listed(dir: &Path, args: &[&str]) -> Vec<String> {
+    let output = run(dir, &[&["list"], args].concat());
+    let mut files: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_check() {
+    let dir = temp_dir("check");
+    write_unsorted(&dir, &["a"]);
+    fs::create_dir_all(dir.join("b")).unwrap();
+    fs::write(dir.join("b/package.json"), SORTED).unwrap();
+
+    let output = run(&dir, &["check", "."]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = stderr(&output);
+    assert!(stderr.contains("✗ Not sorted: a/package.json\n"), "{stderr}");
+    assert!(!stderr.contains("b/package.json"), "{stderr}");
+    assert!(stderr.contains("  Found: 2\n  Not sorted: 1\n  Errors: 0\n"), "{stderr}");
+    assert_eq!(stdout(&output), "");
+    // Nothing is written.
+    assert_eq!(fs::read_to_string(dir.join("a/package.json")).unwrap(), UNSORTED);
+    assert_eq!(sort_package_json(&dir, &["--check", "b"]), Some(0));
+
+    // The change needed, on stdout
+    let output = run(&dir, &["check", "--diff", "--color", "never", "a"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "--- a/a/package.json\n+++ b/a/package.json\n@@ -1 +1,4 @@\n\
+         -{\"version\":\"1.0.0\",\"name\":\"a\"}\n\\ No newline at end of file\n\
+         +{\n+  \"name\": \"a\",\n+  \"version\": \"1.0.0\"\n+}\n"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_xml_reports_escape() {
+    let dir = temp_dir("xml-reports");
+    fs::create_dir_all(dir.join("a&b'x")).unwrap();
+    fs::write(dir.join("a&b'x/package.json"), r#"{"version":"1.0.0","name":"a"}"#).unwrap();
+    let args = ["check", "--continue-on-error", "--diff", "--reporter"];
+
+    let output = run(&dir, &[&args[..], &["checkstyle", "."]].concat());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("<file name=\"a&amp;b&apos;x/package.json\">"));
+
+    let output = run(&dir, &[&args[..], &["junit", "."]].concat());
+    let junit = stdout(&output);
+    assert!(junit.contains("tests=\"1\" failures=\"1\" errors=\"0\""), "{junit}");
+    assert!(junit.contains("<testcase name=\"a&amp;b&apos;x/package.json\""), "{junit}");
+    assert!(junit.contains("+  &quot;name&quot;: &quot;a&quot;,\n"), "{junit}");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_xml_reports_escape_errors() {
+    let dir = temp_dir("xml-errors");
+    fs::create_dir_all(dir.join("<c>")).unwrap();
+    fs::write(dir.join("<c>/package.json"), "{").unwrap();
+
+    let output = run(&dir, &["check", "--reporter", "checkstyle", "."]);
+    assert_eq!(output.status.code(), Some(2));
+    let checkstyle = stdout(&output);
+    assert!(checkstyle.contains("<file name=\"&lt;c&gt;/package.json\">"), "{checkstyle}");
+    assert!(checkstyle.contains("message=\"Failed to parse JSON: "), "{checkstyle}");
+
+    let output = run(&dir, &["check", "--reporter", "junit", "."]);
+    assert!(stdout(&output).contains("tests=\"1\" failures=\"0\" errors=\"1\""));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_json_reports_escape() {
+    let dir = temp_dir("json-reports");
+    for (name, contents) in [
+        ("a \"b\"", r#"{"version":"1.0.0","name":"a"}"#),
+        ("c", "{"),
+        ("sorted", "{\n  \"name\": \"a\"\n}\n"),
+    ] {
+        fs::create_dir_all(dir.join(name)).unwrap();
+        fs::write(dir.join(name).join("package.json"), contents).unwrap();
+    }
+    let args = ["check", "--continue-on-error", "--reporter"];
+
+    let output = run(&dir, &[&args[..], &["sarif", "."]].concat());
+    assert_eq!(output.status.code(), Some(2));
+    let sarif: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    let uri = |rule: &str| {
+        let result = results.iter().find(|result| result["ruleId"] == rule).unwrap();
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"].clone()
+    };
+    assert_eq!(uri("unsorted"), "a \"b\"/package.json");
+    assert_eq!(uri("invalid"), "c/package.json");
+
+    let output = run(&dir, &[&args[..], &["json", "."]].concat());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut files = report["files"].as_array().unwrap().clone();
+    files.sort_by_key(|file| file["path"].as_str().unwrap().to_string());
+    assert_eq!(files[0]["path"], "a \"b\"/package.json");
+    assert_eq!(files[0]["changed"], true);
+    assert!(files[1]["error"].as_str().unwrap().starts_with("Failed to parse JSON: "));
+    assert_eq!(files[2]["changed"], false);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Frames `message` as the language server protocol does.
+fn frame(message: &Value) -> String {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Runs the language server on `input`, returning its exit code and responses.
+fn language_server(input: &str) -> (Option<i32>, Vec<Value>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sort-package-json"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to run sort-package-json");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let mut stdout = std::str::from_utf8(&output.stdout).unwrap();
+    let mut responses = Vec::new();
+    while let Some(rest) = stdout.strip_prefix("Content-Length: ") {
+        let (length, rest) = rest.split_once("\r\n\r\n").unwrap();
+        let (body, rest) = rest.split_at(length.parse().unwrap());
+        responses.push(serde_json::from_str(body).unwrap());
+        stdout = rest;
+    }
+    assert_eq!(stdout, "");
+    (output.status.code(), responses)
+}
+
+fn open(uri: &str, text: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": uri, "text": text } },
+    })
+}
+
+fn formatting(id: u32, uri: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "textDocument/formatting",
+        "params": { "textDocument": { "uri": uri } },
+    })
+}
+
+fn shutdown_and_exit() -> String {
+    let shutdown = json!({ "jsonrpc": "2.0", "id": 0, "method": "shutdown" });
+    let exit = json!({ "jsonrpc": "2.0", "method": "exit" });
+    frame(&shutdown) + &frame(&exit)
+}
+
+#[test]
+fn test_language_server() {
+    let uri = "file:///project/package.json";
+    let text = "{\n  \"version\": \"1.0.0\",\n  \"description\": \"é\",\n  \"name\": \"a\"\n}\n";
+
+    let input = frame(&open(uri, text)) + &frame(&formatting(1, uri)) + &shutdown_and_exit();
+    let (code, responses) = language_server(&input);
+    assert_eq!(code, Some(0));
+    // Only the moved line is edited: `name` goes above `version`.
+    assert_eq!(
+        responses[0]["result"],
+        json!([
+            {
+                "range": {
+                    "start": { "line": 1, "character": 0 },
+                    "end": { "line": 1, "character": 0 },
+                },
+                "newText": "  \"name\": \"a\",\n",
+            },
+            {
+                "range": {
+                    "start": { "line": 2, "character": 0 },
+                    "end": { "line": 4, "character": 0 },
+                },
+                "newText": "  \"description\": \"é\"\n",
+            },
+        ])
+    );
+
+    // Exiting without a `shutdown` request is an error.
+    let exit = json!({ "jsonrpc": "2.0", "method": "exit" });
+    let (code, _) = language_server(&(frame(&open(uri, text)) + &frame(&exit)));
+    assert_eq!(code, Some(5));
+}
+
+#[test]
+fn test_language_server_framing() {
+    let body = formatting(1, "untitled:Untitled-1").to_string();
+    // Other headers are ignored.
+    let message = format!("Content-Length: {}\r\nContent-Type: utf-8\r\n\r\n{}", body.len(), body);
+    let (code, responses) = language_server(&(message.clone() + &message + &shutdown_and_exit()));
+    assert_eq!(code, Some(0));
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0], json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+
+    assert_eq!(language_server(&format!("Content-Type: utf-8\r\n\r\n{}", body)).0, Some(3));
+    // A body cut short by the end of input
+    assert_eq!(language_server("Content-Length: 10\r\n\r\n{}").0, Some(3));
+}
+
+#[test]
+fn test_language_server_decodes_uris() {
+    let dir = temp_dir("lsp-uri");
+    let project = dir.join("b c").join("é");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join(".sortpackagejsonrc.json"), r#"{ "indent": 4 }"#).unwrap();
+    let root = fs::canonicalize(&dir).unwrap();
+    let Some(root) = root.to_str().filter(|root| root.starts_with('/')) else { return };
+
+    // The config next to the decoded path applies.
+    let uri = format!("file://{}/b%20c/%C3%A9/package.json", root);
+    let text = "{\n    \"version\": \"1.0.0\",\n    \"name\": \"a\"\n}\n";
+    let input = frame(&open(&uri, text)) + &frame(&formatting(1, &uri)) + &shutdown_and_exit();
+    let (code, responses) = language_server(&input);
+    assert_eq!(code, Some(0));
+    let new_text = responses[0]["result"][0]["newText"].as_str().unwrap();
+    assert!(new_text.starts_with("    \"name\": \"a\",\n"), "{new_text}");
+
+    // Not a file, or not UTF-8 once decoded: nothing to format.
+    for uri in ["untitled:Untitled-1", "file:///%FF/package.json"] {
+        let input = frame(&open(uri, text)) + &frame(&formatting(1, uri)) + &shutdown_and_exit();
+        assert_eq!(language_server(&input).1[0]["result"], Value::Null);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_exit_code_precedence() {
+    let dir = temp_dir("exit");
+    fs::create_dir_all(dir.join("unsorted")).unwrap();
+    fs::create_dir_all(dir.join("invalid")).unwrap();
+    fs::write(dir.join("unsorted/package.json"), r#"{"version":"1.0.0","name":"a"}"#).unwrap();
+    fs::write(dir.join("invalid/package.json"), "{").unwrap();
+
+    assert_eq!(sort_package_json(&dir, &["check", "unsorted"]), Some(1));
+    assert_eq!(sort_package_json(&dir, &["check", "invalid"]), Some(2));
+    // The highest status wins, whatever order the files are processed in.
+    assert_eq!(sort_package_json(&dir, &["check", "--continue-on-error", "."]), Some(2));
+    assert_eq!(sort_package_json(&dir, &["check", "missing"]), Some(3));
+    assert_eq!(sort_package_json(&dir, &["check", "--threads", "0", "."]), Some(4));
+    assert_eq!(sort_package_json(&dir, &["--help"]), Some(0));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_force_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("force");
+    let file = dir.join("package.json");
+    fs::write(&file, r#"{"version":"1.0.0","name":"a"}"#).unwrap();
+    fs::set_permissions(&file, fs::Permissions::from_mode(0o444)).unwrap();
+
+    assert_eq!(sort_package_json(&dir, &["."]), Some(0));
+    assert!(fs::read_to_string(&file).unwrap().starts_with("{\"version\""));
+
+    assert_eq!(sort_package_json(&dir, &["--force", "."]), Some(0));
+    assert!(fs::read_to_string(&file).unwrap().starts_with("{\n  \"name\""));
+    assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o444);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_config_files() {
+    let dir = temp_dir("config");
+    let file = dir.join("package.json");
+    fs::write(&file, r#"{"version":"1.0.0","name":"a"}"#).unwrap();
+    fs::write(dir.join("sort-package-json.toml"), "indent = 4\n").unwrap();
+    assert_eq!(sort_package_json(&dir, &["."]), Some(0));
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "{\n    \"name\": \"a\",\n    \"version\": \"1.0.0\"\n}\n"
+    );
+
+    fs::remove_file(dir.join("sort-package-json.toml")).unwrap();
+    fs::write(dir.join(".sortpackagejsonrc.json"), r#"{"indent": true}"#).unwrap();
+    let output = run(&dir, &["check", "."]);
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("Error: "), "{stderr}");
+    assert_eq!(stderr.matches("invalid config").count(), 1, "{stderr}");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cache_invalidation() {
+    let dir = temp_dir("cache");
+    let file = dir.join("package.json");
+    let unsorted = r#"{"version":"1.0.0","name":"a"}"#;
+    fs::write(&file, unsorted).unwrap();
+
+    assert_eq!(sort_package_json(&dir, &["--cache", "."]), Some(0));
+    assert!(dir.join(".sortpackagejsoncache").is_file());
+    assert_eq!(sort_package_json(&dir, &["check", "--cache", "."]), Some(0));
+    // Changed contents
+    fs::write(&file, unsorted).unwrap();
+    assert_eq!(sort_package_json(&dir, &["check", "--cache", "."]), Some(1));
+    assert_eq!(sort_package_json(&dir, &["--cache", "."]), Some(0));
+    // Changed options discard the whole record.
+    assert_eq!(sort_package_json(&dir, &["check", "--cache", "--indent", "4", "."]), Some(1));
+    // A corrupt cache starts out empty.
+    fs::write(dir.join("cache.json"), "{").unwrap();
+    let args = ["--cache", "--cache-location", "cache.json", "--indent", "4", "."];
+    assert_eq!(sort_package_json(&dir, &args), Some(0));
+    assert!(fs::read_to_string(&file).unwrap().starts_with("{\n    \"name\""));
+    assert_ne!(fs::read_to_string(dir.join("cache.json")).unwrap(), "{");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_init_round_trips() {
+    let dir = temp_dir("init");
+    assert_eq!(sort_package_json(&dir, &["init"]), Some(0));
+    // An existing config is kept unless --force is given.
+    assert_eq!(sort_package_json(&dir, &["init"]), Some(4));
+    assert_eq!(sort_package_json(&dir, &["init", "--force"]), Some(0));
+
+    let options = SortOptions::from_config(dir.join(".sortpackagejsonrc.json")).unwrap();
+    assert_eq!(format!("{options:?}"), format!("{:?}", SortOptions::default()));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_stdin() {
+    let dir = temp_dir("stdin");
+    let output = run_with_stdin(&dir, &["-"], UNSORTED);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), SORTED);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    let output = run_with_stdin(&dir, &["check", "-"], UNSORTED);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(run_with_stdin(&dir, &["check", "-"], SORTED).status.code(), Some(0));
+
+    let output = run_with_stdin(&dir, &["-"], "{");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Error processing stdin: "), "{}", stderr(&output));
+    // Stdin is sorted on its own.
+    assert_eq!(run_with_stdin(&dir, &["-", "."], UNSORTED).status.code(), Some(4));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_globs() {
+    let dir = temp_dir("globs");
+    write_unsorted(&dir, &[".", "a", "a/b", "c"]);
+
+    assert_eq!(listed(&dir, &["a/**/package.json"]), ["a/b/package.json", "a/package.json"]);
+    // `*` stops at `/`.
+    assert_eq!(listed(&dir, &["*/package.json"]), ["a/package.json", "c/package.json"]);
+    assert_eq!(listed(&dir, &[".", "!a/**"]), ["c/package.json", "package.json"]);
+    assert_eq!(sort_package_json(&dir, &["check", "missing/*.json"]), Some(4));
+
+    // Matches are sorted in place.
+    assert_eq!(sort_package_json(&dir, &["c/*.json"]), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("c/package.json")).unwrap(), SORTED);
+    assert_eq!(fs::read_to_string(dir.join("package.json")).unwrap(), UNSORTED);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_ignore() {
+    let dir = temp_dir("ignore");
+    write_unsorted(&dir, &[".", "fixtures/a", "fixtures/b", "c"]);
+
+    assert_eq!(listed(&dir, &["--ignore", "fixtures/**", "."]), ["c/package.json", "package.json"]);
+    assert_eq!(
+        listed(&dir, &["--ignore", "fixtures/a", "--ignore", "c", "."]),
+        ["fixtures/b/package.json", "package.json"]
+    );
+    // Files named explicitly are skipped too.
+    assert_eq!(listed(&dir, &["--ignore", "c/**", "c/package.json"]), Vec::<String>::new());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_max_depth() {
+    let dir = temp_dir("max-depth");
+    write_unsorted(&dir, &[".", "a", "a/b"]);
+
+    assert_eq!(listed(&dir, &["--max-depth", "1", "."]), ["a/package.json", "package.json"]);
+    assert_eq!(listed(&dir, &["--max-depth", "0", "."]), ["package.json"]);
+    assert_eq!(listed(&dir, &["--no-recursive", "."]), ["package.json"]);
+    // Depth counts from each directory given.
+    assert_eq!(listed(&dir, &["--no-recursive", "a"]), ["a/package.json"]);
+    assert_eq!(listed(&dir, &["."]).len(), 3);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_stdin_filepath() {
+    let dir = temp_dir("stdin-filepath");
+    fs::create_dir_all(dir.join("wide")).unwrap();
+    fs::write(dir.join("wide/.sortpackagejsonrc.json"), r#"{ "indent": 4 }"#).unwrap();
+
+    // The config nearest the path applies, though the file need not exist.
+    let output = run_with_stdin(&dir, &["--stdin-filepath", "wide/package.json"], UNSORTED);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "{\n    \"name\": \"a\",\n    \"version\": \"1.0.0\"\n}\n");
+    let output = run_with_stdin(&dir, &["--stdin-filepath", "package.json"], UNSORTED);
+    assert_eq!(stdout(&output), SORTED);
+
+    // An ignored path passes through unchanged.
+    for args in [
+        &["--stdin-filepath", "node_modules/a/package.json"][..],
+        &["--stdin-filepath", "fixtures/package.json", "--ignore", "fixtures/**"],
+    ] {
+        let output = run_with_stdin(&dir, args, UNSORTED);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output), UNSORTED);
+    }
+
+    // Errors name the path.
+    let output = run_with_stdin(&dir, &["--stdin-filepath", "wide/package.json"], "{");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("wide/package.json: "), "{}", stderr(&output));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Runs git in `dir`, returning its output.
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(["-c", "commit.gpgsign=false"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to run git");
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_changed() {
+    let dir = temp_dir("changed");
+    write_unsorted(&dir, &["a", "b", "c"]);
+    git(&dir, &["init", "-q"]);
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "first"]);
+    fs::write(dir.join("b/package.json"), r#"{"version":"2.0.0","name":"b"}"#).unwrap();
+    git(&dir, &["commit", "-q", "-a", "-m", "second"]);
+
+    assert_eq!(listed(&dir, &["--changed"]), Vec::<String>::new());
+    // Modified and untracked files count as changed.
+    fs::write(dir.join("a/package.json"), r#"{"version":"2.0.0","name":"a"}"#).unwrap();
+    write_unsorted(&dir, &["d"]);
+    assert_eq!(listed(&dir, &["--changed"]), ["a/package.json", "d/package.json"]);
+    assert_eq!(
+        listed(&dir, &["--since", "HEAD~1"]),
+        ["a/package.json", "b/package.json", "d/package.json"]
+    );
+    assert_eq!(listed(&dir, &["--changed", "a", "b"]), ["a/package.json"]);
+    assert_eq!(sort_package_json(&dir, &["check", "--since", "no-such-ref"]), Some(4));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_staged() {
+    let dir = temp_dir("staged");
+    write_unsorted(&dir, &["a", "b", "c"]);
+    git(&dir, &["init", "-q"]);
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "first"]);
+    fs::write(dir.join("a/package.json"), r#"{"version":"2.0.0","name":"a"}"#).unwrap();
+    fs::write(dir.join("b/package.json"), r#"{"version":"2.0.0","name":"b"}"#).unwrap();
+    git(&dir, &["add", "a/package.json"]);
+
+    // Only the staged file is sorted, and the sorted file is staged.
+    assert_eq!(sort_package_json(&dir, &["--staged"]), Some(0));
+    let sorted = "{\n  \"name\": \"a\",\n  \"version\": \"2.0.0\"\n}\n";
+    assert_eq!(git(&dir, &["show", ":a/package.json"]), sorted);
+    assert_eq!(git(&dir, &["status", "--porcelain"]), "M  a/package.json\n M b/package.json\n");
+
+    // A staged file with unstaged changes is left alone.
+    git(&dir, &["add", "b/package.json"]);
+    fs::write(dir.join("b/package.json"), r#"{"version":"3.0.0","name":"b"}"#).unwrap();
+    let output = run(&dir, &["--staged"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("b/package.json has unstaged changes"), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(dir.join("b/package.json")).unwrap(),
+        r#"{"version":"3.0.0","name":"b"}"#
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_backup() {
+    let dir = temp_dir("backup");
+    write_unsorted(&dir, &["a", "b"]);
+    fs::write(dir.join("c.json"), SORTED).unwrap();
+
+    assert_eq!(sort_package_json(&dir, &["--backup", "a"]), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("a/package.json")).unwrap(), SORTED);
+    assert_eq!(fs::read_to_string(dir.join("a/package.json.bak")).unwrap(), UNSORTED);
+
+    assert_eq!(sort_package_json(&dir, &["--backup=.orig", "b"]), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("b/package.json.orig")).unwrap(), UNSORTED);
+    assert!(!dir.join("b/package.json.bak").exists());
+
+    // Files that are already sorted are not rewritten, so need no backup.
+    assert_eq!(sort_package_json(&dir, &["--backup", "--write", "c.json"]), Some(0));
+    assert!(!dir.join("c.json.bak").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let dir = temp_dir("symlinks");
+    let project = dir.join("project");
+    write_unsorted(&dir, &["outside", "project/real"]);
+    fs::create_dir_all(project.join("linked")).unwrap();
+    fs::create_dir_all(project.join("inside")).unwrap();
+    symlink(dir.join("outside/package.json"), project.join("linked/package.json")).unwrap();
+    symlink("../real/package.json", project.join("inside/package.json")).unwrap();
+
+    // A link out of the current directory is skipped; one inside it is processed once.
+    let files = listed(&project, &["."]);
+    assert!(files == ["inside/package.json"] || files == ["real/package.json"], "{files:?}");
+    assert_eq!(listed(&project, &["--no-follow-symlinks", "."]), ["real/package.json"]);
+    assert_eq!(listed(&project, &["--follow-symlinks", "linked"]), ["linked/package.json"]);
+
+    // Writing through a link replaces the target and keeps the link.
+    assert_eq!(sort_package_json(&project, &["--follow-symlinks", "linked"]), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("outside/package.json")).unwrap(), SORTED);
+    assert!(fs::symlink_metadata(project.join("linked/package.json")).unwrap().is_symlink());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_ignore_path() {
+    let dir = temp_dir("ignore-path");
+    write_unsorted(&dir, &[".", "fixtures/a", "fixtures/keep", "c"]);
+    fs::write(dir.join(".prettierignore"), "fixtures/\n").unwrap();
+    fs::write(dir.join(".otherignore"), "c\n").unwrap();
+
+    let prettier = ["--ignore-path", ".prettierignore"];
+    assert_eq!(listed(&dir, &[&prettier[..], &["."]].concat()), ["c/package.json", "package.json"]);
+    let both = [&prettier[..], &["--ignore-path", ".otherignore", "."]].concat();
+    assert_eq!(listed(&dir, &both), ["package.json"]);
+    // Not one of the ignore files --no-ignore turns off
+    assert_eq!(listed(&dir, &[&prettier[..], &["--no-ignore", "."]].concat()).len(), 2);
+    let output = run_with_stdin(
+        &dir,
+        &[&prettier[..], &["--stdin-filepath", "fixtures/a/package.json"]].concat(),
+        UNSORTED,
+    );
+    assert_eq!(stdout(&output), UNSORTED);
+    assert_eq!(sort_package_json(&dir, &["check", "--ignore-path", "missing", "."]), Some(4));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_daemon() {
+    let dir = temp_dir("daemon");
+    write_unsorted(&dir, &["wide"]);
+    fs::write(dir.join("wide/.sortpackagejsonrc.json"), r#"{ "indent": 4 }"#).unwrap();
+    let requests = [
+        json!({ "path": "package.json", "contents": UNSORTED }),
+        json!({ "path": "package.json", "contents": SORTED }),
+        // Read from disk, with the config nearest the path
+        json!({ "path": "wide/package.json" }),
+        json!({ "path": "missing/package.json" }),
+        json!({ "contents": UNSORTED }),
+    ];
+    let mut input: String = requests.iter().map(|request| format!("{request}\n")).collect();
+    input.push_str("not json\n");
+
+    let output = run_with_stdin(&dir, &["--daemon"], &input);
+    assert_eq!(output.status.code(), Some(0));
+    let responses: Vec<Value> =
+        stdout(&output).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(responses.len(), 6);
+    assert_eq!(responses[0], json!({ "output": SORTED, "changed": true }));
+    assert_eq!(responses[1], json!({ "output": SORTED, "changed": false }));
+    assert_eq!(responses[2]["output"], "{\n    \"name\": \"a\",\n    \"version\": \"1.0.0\"\n}\n");
+    for response in &responses[3..] {
+        assert!(response["error"].is_string(), "{response}");
+    }
+    // The daemon never writes files.
+    assert_eq!(fs::read_to_string(dir.join("wide/package.json")).unwrap(), UNSORTED);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_include() {
+    let dir = temp_dir("include");
+    write_unsorted(&dir, &["a", "b"]);
+    for name in ["a/package.json.tmpl", "b/package.template.json", "b/other.json"] {
+        fs::write(dir.join(name), UNSORTED).unwrap();
+    }
+
+    assert_eq!(
+        listed(&dir, &["--include", "package.json.tmpl", "."]),
+        ["a/package.json", "a/package.json.tmpl", "b/package.json"]
+    );
+    assert_eq!(
+        listed(&dir, &["--include", "*.tmpl", "--include", "package.*.json", "b"]),
+        ["b/package.json", "b/package.template.json"]
+    );
+    assert_eq!(sort_package_json(&dir, &["--include", "*.tmpl", "."]), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("a/package.json.tmpl")).unwrap(), SORTED);
+    assert_eq!(fs::read_to_string(dir.join("b/other.json")).unwrap(), UNSORTED);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "json5")]
+#[test]
+fn test_commented_manifests() {
+    let dir = temp_dir("commented-manifests");
+    write_unsorted(&dir, &["a", "b"]);
+    fs::write(
+        dir.join("a/package.jsonc"),
+        "{\n  \"version\": \"1.0.0\", // bumped\n  \"name\": \"a\",\n}\n",
+    )
+    .unwrap();
+    fs::write(dir.join("b/package.json5"), "// b\n{version: '1.0.0', name: 'b'}\n").unwrap();
+
+    assert_eq!(
+        listed(&dir, &["."]),
+        ["a/package.json", "a/package.jsonc", "b/package.json", "b/package.json5"]
+    );
+    assert_eq!(sort_package_json(&dir, &["."]), Some(0));
+    assert_eq!(
+        fs::read_to_string(dir.join("a/package.jsonc")).unwrap(),
+        "{\n  \"name\": \"a\",\n  \"version\": \"1.0.0\" // bumped\n}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("b/package.json5")).unwrap(),
+        "// b\n{\n  name: 'b',\n  version: '1.0.0'\n}\n"
+    );
+    assert_eq!(sort_package_json(&dir, &["--check", "."]), Some(0));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_output() {
+    let dir = temp_dir("output");
+    write_unsorted(&dir, &["."]);
+
+    let output = run(&dir, &["package.json", "--output", "sorted.json"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(fs::read_to_string(dir.join("sorted.json")).unwrap(), SORTED);
+    assert_eq!(fs::read_to_string(dir.join("package.json")).unwrap(), UNSORTED);
+
+    assert_eq!(run_with_stdin(&dir, &["-", "-o", "stdin.json"], UNSORTED).status.code(), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("stdin.json")).unwrap(), SORTED);
+
+    // Only for a single file
+    assert_eq!(sort_package_json(&dir, &[".", "-o", "out.json"]), Some(4));
+    assert_eq!(sort_package_json(&dir, &["package.json", "--write", "-o", "out.json"]), Some(4));
+    assert!(!dir.join("out.json").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_interactive() {
+    let dir = temp_dir("interactive");
+    let dirs = ["a", "b", "c", "d", "e"];
+    let read = |name: &str| fs::read_to_string(dir.join(name).join("package.json")).unwrap();
+    write_unsorted(&dir, &dirs);
+
+    // An unknown answer is asked again; `a` sorts the rest.
+    let output = run_with_stdin(&dir, &[&["--interactive"], &dirs[..3]].concat(), "x\ny\nn\na\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output).matches("Sort a/package.json? [y,n,a,q]").count(), 2);
+    assert!(stderr(&output).contains("  Sorted: 2\n  Declined: 1\n"), "{}", stderr(&output));
+    assert!(stdout(&output).contains("+++ b/a/package.json\n"));
+    assert_eq!([read("a"), read("b"), read("c")], [SORTED, UNSORTED, SORTED]);
+
+    // `q`, like the end of input, skips the rest.
+    let output = run_with_stdin(&dir, &["--interactive", "b", "d"], "q\n");
+    assert!(!stderr(&output).contains("Sort d/package.json?"), "{}", stderr(&output));
+    run_with_stdin(&dir, &["--interactive", "e"], "");
+    assert_eq!([read("b"), read("d"), read("e")], [UNSORTED, UNSORTED, UNSORTED]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_ci() {
+    let dir = temp_dir("ci");
+    write_unsorted(&dir, &["a", "b"]);
+    fs::create_dir_all(dir.join("c")).unwrap();
+    fs::write(dir.join("c/package.json"), "{").unwrap();
+    fs::create_dir_all(dir.join("d")).unwrap();
+    fs::write(dir.join("d/package.json"), SORTED).unwrap();
+
+    // Checks every file, however early one fails, without writing or coloring anything.
+    let output = run(&dir, &["--ci", "--color", "always", "."]);
+    assert_eq!(output.status.code(), Some(2));
+    let lines = stderr(&output);
+    assert!(lines.ends_with("\nsummary: found=4 unsorted=2 errors=1\n"), "{lines}");
+    assert!(!lines.contains('\x1b'), "{lines}");
+    assert_eq!(fs::read_to_string(dir.join("a/package.json")).unwrap(), UNSORTED);
+
+    fs::remove_dir_all(dir.join("c")).unwrap();
+    let output = run(&dir, &["--ci", "d"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output), "\nsummary: found=1 unsorted=0 errors=0\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_vendor_dirs() {
+    let dir = temp_dir("vendor");
+    let vendored = ["a/node_modules/b", "bower_components/c", "jspm_packages/d", "node_modules/e"];
+    write_unsorted(&dir, &[&["."], &vendored[..]].concat());
+
+    // Skipped without any ignore file
+    assert_eq!(listed(&dir, &["."]), ["package.json"]);
+    assert_eq!(listed(&dir, &["**/package.json"]), ["package.json"]);
+    assert_eq!(listed(&dir, &["--include-node-modules", "."]).len(), 5);
+    // A vendor directory given by name is searched.
+    assert_eq!(listed(&dir, &["node_modules"]), ["node_modules/e/package.json"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_files_from() {
+    let dir = temp_dir("files-from");
+    write_unsorted(&dir, &[".", "a", "b", "c"]);
+    fs::write(dir.join("list.txt"), "a/package.json\r\n\nc\n").unwrap();
+
+    assert_eq!(listed(&dir, &["--files-from", "list.txt"]), ["a/package.json", "c/package.json"]);
+    assert_eq!(
+        listed(&dir, &["--files-from", "list.txt", "b"]),
+        ["a/package.json", "b/package.json", "c/package.json"]
+    );
+    let output = run_with_stdin(&dir, &["list", "--files-from", "-"], "b/package.json\n");
+    assert_eq!(stdout(&output), "b/package.json\n");
+    // An empty list sorts nothing rather than the current directory.
+    let output = run_with_stdin(&dir, &["--files-from", "-"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("package.json")).unwrap(), UNSORTED);
+    assert_eq!(sort_package_json(&dir, &["--files-from", "missing.txt"]), Some(3));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,10 +1,21 @@
 use serde_json::Value;
-use sort_package_json::{SortOptions, sort_package_json_with_options};
+use sort_package_json::{
+    Config, Diagnostic, ExportsMode, FieldOrder, FieldRule, FieldStrategy, Indent, KeyComparator,
+    LineEnding, PrivateFieldPlacement, RemovedEntry, SortError, SortOptions, SortedPackageJson,
+    Sorter, TrailingNewline, Visitor, default_field_order, diff_package_json, find_config_file,
+    is_package_json_sorted, ruleset_version, sort_dependencies, sort_exports, sort_from_reader,
+    sort_many, sort_package_json_edits, sort_package_json_into, sort_package_json_with_options,
+    sort_package_json_with_outcome, sort_package_json_with_report, sort_package_json_with_visitor,
+    sort_scripts, unified_diff,
+};
 use std::fs;
 
 fn sort(s: &str) -> String {
-    sort_package_json_with_options(s, &SortOptions { pretty: true, sort_scripts: true })
-        .expect("Failed to parse package.json")
+    sort_package_json_with_options(
+        s,
+        &SortOptions { pretty: true, sort_scripts: true, ..SortOptions::default() },
+    )
+    .expect("Failed to parse package.json")
 }
 
 #[test]
@@ -96,3 +107,1221 @@ fn test_utf8_bom_preservation() {
     let second_sort = sort(&result);
     assert_eq!(result, second_sort, "Sorting BOM files should be idempotent");
 }
+
+#[test]
+fn test_errors() {
+    let options = SortOptions::default();
+
+    let err = sort_package_json_with_options("{\n  \"name\": \"test\",\n}", &options).unwrap_err();
+    assert!(matches!(err, SortError::Parse { line: 3, column: 1, .. }), "{err:?}");
+
+    for input in ["[]", "\"name\"", "1", "null"] {
+        let err = sort_package_json_with_options(input, &options).unwrap_err();
+        assert!(matches!(err, SortError::NotAnObject), "{input}: {err:?}");
+    }
+}
+
+#[test]
+fn test_outcome() {
+    let options = SortOptions::default();
+
+    let outcome =
+        sort_package_json_with_outcome(r#"{"version": "1.0.0", "name": "test"}"#, &options)
+            .expect("Failed to parse package.json");
+    assert!(outcome.changed);
+
+    let outcome = sort_package_json_with_outcome(&outcome.output, &options)
+        .expect("Failed to parse package.json");
+    assert!(!outcome.changed, "Sorted output should be reported as unchanged");
+}
+
+#[test]
+fn test_is_sorted() {
+    let options = SortOptions::default();
+    let is_sorted = |s: &str| is_package_json_sorted(s, &options).expect("Failed to parse");
+
+    assert!(!is_sorted(r#"{"version": "1.0.0", "name": "test"}"#));
+    assert!(is_sorted(r#"{"name": "test", "version": "1.0.0"}"#));
+    // Nested transforms count too, not just top-level order.
+    assert!(!is_sorted(r#"{"dependencies": {"b": "1", "a": "1"}}"#));
+    assert!(!is_sorted(r#"{"keywords": ["a", "a"]}"#));
+    // Formatting is not checked.
+    assert!(is_sorted(r#"{"name":"test","dependencies":{"a":"1","b":"1"}}"#));
+
+    let input = fs::read_to_string("tests/fixtures/package.json").expect("Failed to read fixture");
+    assert!(!is_sorted(&input));
+    assert!(is_sorted(&sort_package_json_with_options(&input, &options).unwrap()));
+}
+
+/// Option sets that change what sorted means, for cross-checking `is_package_json_sorted`.
+fn check_option_variants() -> Vec<SortOptions> {
+    let base = SortOptions { pretty: false, ..SortOptions::default() };
+    vec![
+        base.clone(),
+        SortOptions { normalize: true, ..base.clone() },
+        SortOptions { sort_scripts: true, ..base.clone() },
+        SortOptions { natural_sort: true, ..base.clone() },
+        SortOptions { case_insensitive_dependencies: true, ..base.clone() },
+        SortOptions { sort_dependencies: false, ..base.clone() },
+        SortOptions { sort_files: false, dedupe_arrays: false, ..base.clone() },
+        SortOptions { exports_mode: ExportsMode::GroupPaths, ..base.clone() },
+        SortOptions { exports_mode: ExportsMode::Off, ..base.clone() },
+        SortOptions { remove_empty_fields: true, ..base.clone() },
+        SortOptions { top_level_only: true, ..base.clone() },
+        SortOptions { comparator: Some(KeyComparator::new(|a, b| b.cmp(a))), ..base.clone() },
+        SortOptions {
+            field_strategies: [
+                ("bin".to_string(), FieldStrategy::Preserve),
+                ("scripts".to_string(), FieldStrategy::Recursive),
+                ("engines".to_string(), FieldStrategy::KeyOrder(vec!["node".into()])),
+                ("custom".to_string(), FieldStrategy::Alphabetical),
+            ]
+            .into_iter()
+            .collect(),
+            ..base
+        },
+    ]
+}
+
+#[test]
+fn test_is_sorted_agrees_with_sort() {
+    let values = [
+        r#""x""#,
+        "1",
+        "{}",
+        "[]",
+        r#"["b", "a"]"#,
+        r#"["a", "b"]"#,
+        r#"["a", "a"]"#,
+        r#"["a", 1, "b"]"#,
+        r#"["a10", "a2"]"#,
+        r#"{"b": 1, "a": {"d": 1, "c": 2}}"#,
+        r#"{"a": {"d": 1, "c": 2}, "b": 1}"#,
+        r#"{"a": {"c": 1, "d": 2}, "b": [{"d": 1, "c": 2}]}"#,
+        r#"{"B": 1, "a": 2}"#,
+        r#"{"a": 2, "B": 1}"#,
+        r#"{"url": "u", "type": "git"}"#,
+        r#"{"type": "git", "url": "u", "a": 1}"#,
+        r#"{"name": "n", "email": "e"}"#,
+        r#"{"email": "e", "name": "n"}"#,
+        r#"{"test": "t", "lib": "l"}"#,
+        r#"{"host": "h", "module_name": "m"}"#,
+        r#"{"./a": "2", ".": "1"}"#,
+        r#"{".": "1", "./a": "2", "./b": {"require": "r", "import": "i"}}"#,
+        r#"{"pkg": {"optional": true, "a": 1}, "a": {"x": 1}}"#,
+        r#"{"pkg": {"a": 1, "optional": true}}"#,
+        r#"{"pkg": {"scheduler": "1", ".": "18"}}"#,
+        r#"{"pkg": {".": "18", "scheduler": "1"}}"#,
+        r#"{"hooks": {"pre-push": "a", "pre-commit": "b"}, "a": 1}"#,
+        r#"{"a": 1, "hooks": {"pre-commit": {"y": 1, "x": 2}, "pre-push": "a"}}"#,
+        r#"{"pre-commit": "b", "pre-push": "a", "custom": 1}"#,
+        r#"{"pkg/**/x": 1, "pkg-a": 2, "pkg": 3}"#,
+        r#"{"pkg": 3, "pkg/**/x": 1, "pkg-a": 2}"#,
+        r#"{"version": "1", "name": "n", "dependencies": {"b": "1", "a": "1"}}"#,
+        r#""Jane <jane@example.com>""#,
+        r#"[{"email": "e", "name": "n"}, "Jane <jane@example.com>"]"#,
+        r#""github:a/b""#,
+    ];
+    let mut fields: Vec<&str> = default_field_order().to_vec();
+    fields.extend(["custom", "_private"]);
+    for options in check_option_variants() {
+        for field in &fields {
+            for value in values {
+                let value: Value = serde_json::from_str(value).unwrap();
+                let input = serde_json::json!({ *field: value }).to_string();
+                let sorted = sort_package_json_with_options(&input, &options).unwrap();
+                assert_eq!(
+                    is_package_json_sorted(&input, &options).unwrap(),
+                    sorted == input,
+                    "{input} with {options:?}"
+                );
+            }
+        }
+    }
+
+    let keys = ["_id", "zzz", "aaa", "_b", "dependencies", "devDependencies"];
+    let placements = [
+        PrivateFieldPlacement::First,
+        PrivateFieldPlacement::Last,
+        PrivateFieldPlacement::Preserve,
+        PrivateFieldPlacement::Merge,
+    ];
+    for placement in placements {
+        for field_order in [
+            FieldOrder::Canonical,
+            FieldOrder::Alphabetical,
+            FieldOrder::Custom(vec!["zzz".into(), "_id".into()]),
+        ] {
+            for (sort_unknown_fields, dependency_order) in
+                [(true, vec![]), (false, vec!["devDependencies".to_string()])]
+            {
+                let options = SortOptions {
+                    pretty: false,
+                    private_fields: placement,
+                    field_order: field_order.clone(),
+                    sort_unknown_fields,
+                    dependency_order,
+                    ..SortOptions::default()
+                };
+                for_each_permutation(&mut keys.clone(), 0, &mut |keys| {
+                    let input = serde_json::Value::Object(
+                        keys.iter().map(|key| (key.to_string(), Value::from(1))).collect(),
+                    )
+                    .to_string();
+                    let sorted = sort_package_json_with_options(&input, &options).unwrap();
+                    assert_eq!(
+                        is_package_json_sorted(&input, &options).unwrap(),
+                        sorted == input,
+                        "{input} with {options:?}"
+                    );
+                });
+            }
+        }
+    }
+}
+
+fn for_each_permutation(keys: &mut [&str], start: usize, f: &mut impl FnMut(&[&str])) {
+    if start == keys.len() {
+        f(keys);
+        return;
+    }
+    for i in start..keys.len() {
+        keys.swap(start, i);
+        for_each_permutation(keys, start + 1, f);
+        keys.swap(start, i);
+    }
+}
+
+#[test]
+fn test_report() {
+    let input = r#"{
+  "version": "1.0.0",
+  "name": "test",
+  "keywords": ["b", "a", "b"],
+  "dependencies": {"b": "1", "a": "1"},
+  "scripts": {"test": "jest"}
+}"#;
+    let report = sort_package_json_with_report(input, &SortOptions::default()).unwrap();
+
+    assert_eq!(
+        report.output,
+        sort_package_json_with_options(input, &SortOptions::default()).unwrap()
+    );
+    assert_eq!(report.reordered_keys, ["name", "scripts"]);
+    assert_eq!(report.transformed_fields, ["keywords", "dependencies"]);
+    assert_eq!(
+        report.removed_entries,
+        [RemovedEntry { field: "keywords".into(), value: Value::from("b") }]
+    );
+    assert!(report.diagnostics.is_empty());
+}
+
+#[test]
+fn test_diff() {
+    let options = SortOptions::default();
+    let input = "{\n  \"version\": \"1.0.0\",\n  \"name\": \"test\"\n}\n";
+
+    let diff = diff_package_json(input, &options).unwrap().expect("Input is not sorted");
+    insta::assert_snapshot!(diff);
+
+    let sorted = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(diff_package_json(&sorted, &options).unwrap(), None);
+    assert_eq!(unified_diff(input, &sorted, "package.json"), diff);
+
+    // A replaced block shows every removed line before the added ones.
+    let diff =
+        unified_diff("{\"b\":1,\"a\":2}\n", "{\n  \"a\": 2,\n  \"b\": 1\n}\n", "package.json");
+    assert_eq!(
+        diff,
+        "--- a/package.json\n+++ b/package.json\n@@ -1 +1,4 @@\n-{\"b\":1,\"a\":2}\n+{\n+  \"a\": 2,\n+  \"b\": 1\n+}\n"
+    );
+}
+
+#[test]
+fn test_dedupe_arrays_disabled() {
+    let input = r#"{"keywords": ["b", "a", "b"], "files": ["dist", "src", "dist"]}"#;
+    let options = SortOptions { pretty: false, dedupe_arrays: false, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(result, r#"{"keywords":["a","b","b"],"files":["dist","src","dist"]}"#);
+}
+
+#[test]
+fn test_unknown_fields_unsorted() {
+    let input = r#"{"zeta": 1, "_b": 1, "alpha": 1, "_a": 1, "name": "test"}"#;
+    let options =
+        SortOptions { pretty: false, sort_unknown_fields: false, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(result, r#"{"name":"test","zeta":1,"alpha":1,"_b":1,"_a":1}"#);
+}
+
+#[test]
+fn test_private_field_placement() {
+    let input = r#"{"_id": 1, "zeta": 1, "name": "test", "_resolved": 1, "alpha": 1}"#;
+    let sort = |private_fields| {
+        let options = SortOptions { pretty: false, private_fields, ..SortOptions::default() };
+        sort_package_json_with_options(input, &options).unwrap()
+    };
+
+    assert_eq!(
+        sort(PrivateFieldPlacement::Last),
+        r#"{"name":"test","alpha":1,"zeta":1,"_id":1,"_resolved":1}"#
+    );
+    assert_eq!(
+        sort(PrivateFieldPlacement::First),
+        r#"{"_id":1,"_resolved":1,"name":"test","alpha":1,"zeta":1}"#
+    );
+    assert_eq!(
+        sort(PrivateFieldPlacement::Preserve),
+        r#"{"_id":1,"name":"test","alpha":1,"_resolved":1,"zeta":1}"#
+    );
+    assert_eq!(
+        sort(PrivateFieldPlacement::Merge),
+        r#"{"name":"test","_id":1,"_resolved":1,"alpha":1,"zeta":1}"#
+    );
+
+    // A private field in the custom order is placed by it, not preserved.
+    let options = SortOptions {
+        pretty: false,
+        private_fields: PrivateFieldPlacement::Preserve,
+        field_order: FieldOrder::Custom(vec!["_resolved".into()]),
+        ..SortOptions::default()
+    };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"_id":1,"_resolved":1,"name":"test","alpha":1,"zeta":1}"#
+    );
+}
+
+#[test]
+fn test_custom_comparator() {
+    let input = r#"{"beta": 1, "Alpha": 1, "dependencies": {"b": "1", "A": "1", "c": "1"}, "keywords": ["b", "A"]}"#;
+    let options = SortOptions {
+        pretty: false,
+        comparator: Some(KeyComparator::new(|a, b| {
+            a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b))
+        })),
+        ..SortOptions::default()
+    };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(
+        result,
+        r#"{"keywords":["A","b"],"dependencies":{"A":"1","b":"1","c":"1"},"Alpha":1,"beta":1}"#
+    );
+}
+
+#[test]
+fn test_field_strategies() {
+    let input = r#"{
+  "custom": {"b": {"d": 1, "c": 1}, "a": 1},
+  "dependencies": {"b": "1", "a": "1"},
+  "repository": {"url": "x", "type": "git", "directory": "pkg"},
+  "name": "test"
+}"#;
+    let options = SortOptions {
+        pretty: false,
+        field_strategies: [
+            ("custom".to_string(), FieldStrategy::Recursive),
+            ("dependencies".to_string(), FieldStrategy::Preserve),
+            ("repository".to_string(), FieldStrategy::KeyOrder(vec!["directory".to_string()])),
+        ]
+        .into_iter()
+        .collect(),
+        ..SortOptions::default()
+    };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(
+        result,
+        r#"{"name":"test","repository":{"directory":"pkg","type":"git","url":"x"},"dependencies":{"b":"1","a":"1"},"custom":{"a":1,"b":{"c":1,"d":1}}}"#
+    );
+}
+
+#[test]
+fn test_field_rules() {
+    struct Proprietary;
+
+    impl FieldRule for Proprietary {
+        fn applies(&self, key: &str) -> bool {
+            key.starts_with("acme")
+        }
+
+        fn transform(&self, value: Value) -> Value {
+            match value {
+                Value::Array(mut arr) => {
+                    arr.reverse();
+                    Value::Array(arr)
+                }
+                other => other,
+            }
+        }
+
+        fn order(&self) -> Option<usize> {
+            Some(1) // right after `name`
+        }
+    }
+
+    let sorter = Sorter::default().with_rule(Proprietary);
+    let input = r#"{"version": "1.0.0", "acmeTargets": [1, 2], "name": "test", "zeta": 1}"#;
+    let result: Value = serde_json::from_str(&sorter.sort(input).unwrap()).unwrap();
+    let keys: Vec<&str> = result.as_object().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(keys, ["name", "acmeTargets", "version", "zeta"]);
+    assert_eq!(result["acmeTargets"], serde_json::json!([2, 1]));
+}
+
+#[test]
+fn test_default_field_order() {
+    let order = default_field_order();
+    assert_eq!(order.first(), Some(&"$schema"));
+    // Changing the field table must come with a `ruleset_version` bump.
+    assert_eq!((ruleset_version(), order.len()), ("14", 141));
+
+    // Sorting every known field, given in reverse, must reproduce the published order.
+    let input: serde_json::Map<String, Value> =
+        order.iter().rev().map(|key| ((*key).to_string(), Value::Null)).collect();
+    let result = sort(&Value::Object(input).to_string());
+    let parsed: serde_json::Map<String, Value> = serde_json::from_str(&result).unwrap();
+    assert!(parsed.keys().eq(order.iter().copied()));
+}
+
+#[test]
+fn test_section_toggles() {
+    let input = r#"{
+  "exports": {"./utils": "./utils.js", ".": {"require": "./index.cjs", "import": "./index.mjs"}},
+  "files": ["dist", "dist"],
+  "dependencies": {"b": "1", "a": "1"}
+}"#;
+
+    let defaults = SortOptions { pretty: false, ..SortOptions::default() };
+    assert_eq!(
+        sort_package_json_with_options(input, &defaults).unwrap(),
+        r#"{"files":["dist"],"exports":{"./utils":"./utils.js",".":{"require":"./index.cjs","import":"./index.mjs"}},"dependencies":{"a":"1","b":"1"}}"#
+    );
+
+    let options = SortOptions {
+        sort_dependencies: false,
+        sort_files: false,
+        exports_mode: ExportsMode::GroupPaths,
+        ..defaults.clone()
+    };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"files":["dist","dist"],"exports":{".":{"require":"./index.cjs","import":"./index.mjs"},"./utils":"./utils.js"},"dependencies":{"b":"1","a":"1"}}"#
+    );
+
+    let options = SortOptions { exports_mode: ExportsMode::Off, ..defaults };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"files":["dist"],"exports":{".":{"import":"./index.mjs","require":"./index.cjs"},"./utils":"./utils.js"},"dependencies":{"a":"1","b":"1"}}"#
+    );
+}
+
+#[test]
+fn test_dependencies_meta() {
+    let input = r#"{
+  "peerDependenciesMeta": {"react": {"x": 1, "optional": true}, "@types/react": {"optional": true}}
+}"#;
+    let options = SortOptions { pretty: false, ..SortOptions::default() };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"peerDependenciesMeta":{"@types/react":{"optional":true},"react":{"optional":true,"x":1}}}"#
+    );
+
+    let input = r#"{
+  "dependenciesMeta": {"fsevents@2.3.2": {"unplugged": true, "built": false}, "core-js": {"built": false}}
+}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"dependenciesMeta":{"core-js":{"built":false},"fsevents@2.3.2":{"built":false,"unplugged":true}}}"#
+    );
+}
+
+#[test]
+fn test_override_fields() {
+    let input = r#"{
+  "overrides": {"react": {"scheduler": "1", ".": "18"}, "foo": {"bar": {"baz": "1", ".": "2"}}}
+}"#;
+    let options = SortOptions { pretty: false, ..SortOptions::default() };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"overrides":{"foo":{"bar":{".":"2","baz":"1"}},"react":{".":"18","scheduler":"1"}}}"#
+    );
+
+    let input = r#"{"resolutions": {"pkg-a": "3", "pkg/**/left-pad": "1", "lib10": "1", "lib2": "1", "pkg": "2"}}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"resolutions":{"lib2":"1","lib10":"1","pkg":"2","pkg/**/left-pad":"1","pkg-a":"3"}}"#
+    );
+    let reversed =
+        SortOptions { comparator: Some(KeyComparator::new(|a, b| b.cmp(a))), ..options.clone() };
+    assert_eq!(
+        sort_package_json_with_options(input, &reversed).unwrap(),
+        r#"{"resolutions":{"pkg-a":"3","pkg":"2","pkg/**/left-pad":"1","lib2":"1","lib10":"1"}}"#
+    );
+
+    let input = r#"{"resolutions": {"react-dom": "1", "React/scheduler": "1", "a": "1"}}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"resolutions":{"React/scheduler":"1","a":"1","react-dom":"1"}}"#
+    );
+    let case_insensitive = SortOptions { case_insensitive_dependencies: true, ..options.clone() };
+    assert_eq!(
+        sort_package_json_with_options(input, &case_insensitive).unwrap(),
+        r#"{"resolutions":{"a":"1","React/scheduler":"1","react-dom":"1"}}"#
+    );
+}
+
+#[test]
+fn test_git_hook_configs() {
+    let input = r#"{
+  "lint-staged": {"*.{ts,tsx}": ["tsc", "eslint --fix"], "*.10.js": "b", "*.2.js": "a", "*.css": "stylelint"}
+}"#;
+    let options = SortOptions { pretty: false, ..SortOptions::default() };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"lint-staged":{"*.2.js":"a","*.10.js":"b","*.css":"stylelint","*.{ts,tsx}":["tsc","eslint --fix"]}}"#
+    );
+
+    let input =
+        r#"{"nano-staged": {"*.md": "prettier --write", "*.js": ["eslint", "prettier --write"]}}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"nano-staged":{"*.js":["eslint","prettier --write"],"*.md":"prettier --write"}}"#
+    );
+
+    let input = r#"{"lint-staged": {"src-legacy/*.js": "a", "src/**/*.ts": "b", "*.css": "c"}}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"lint-staged":{"*.css":"c","src/**/*.ts":"b","src-legacy/*.js":"a"}}"#
+    );
+    let reversed =
+        SortOptions { comparator: Some(KeyComparator::new(|a, b| b.cmp(a))), ..options.clone() };
+    assert_eq!(
+        sort_package_json_with_options(input, &reversed).unwrap(),
+        r#"{"lint-staged":{"src-legacy/*.js":"a","src/**/*.ts":"b","*.css":"c"}}"#
+    );
+
+    let input = r#"{"simple-git-hooks": {"pre-push": "c", "preserveUnused": true, "commit-msg": "b", "pre-commit": "a"}}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"simple-git-hooks":{"pre-commit":"a","commit-msg":"b","pre-push":"c","preserveUnused":true}}"#
+    );
+
+    let input = r#"{"husky": {"skipCI": true, "hooks": {"pre-push": "c", "commit-msg": "b", "pre-commit": "a"}}}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"husky":{"hooks":{"pre-commit":"a","commit-msg":"b","pre-push":"c"},"skipCI":true}}"#
+    );
+
+    let input = r#"{"pre-commit": {"silent": true, "run": ["test", "lint"]}}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"pre-commit":{"run":["test","lint"],"silent":true}}"#
+    );
+    let input = r#"{"pre-commit": ["test", "lint"]}"#;
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"pre-commit":["test","lint"]}"#
+    );
+}
+
+#[test]
+fn test_natural_sort() {
+    let input = r#"{
+  "scripts": {"test:10": "a", "test:2": "b", "test:1": "c"},
+  "keywords": ["chunk10", "chunk2", "chunk02", "chunk"]
+}"#;
+    let options = SortOptions {
+        pretty: false,
+        sort_scripts: true,
+        natural_sort: true,
+        ..SortOptions::default()
+    };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"keywords":["chunk","chunk02","chunk2","chunk10"],"scripts":{"test:1":"c","test:2":"b","test:10":"a"}}"#
+    );
+}
+
+#[test]
+fn test_case_insensitive_dependencies() {
+    let input =
+        r#"{"dependencies": {"through": "1", "JSONStream": "1", "jsonstream": "1", "async": "1"}}"#;
+    let options = SortOptions {
+        pretty: false,
+        case_insensitive_dependencies: true,
+        ..SortOptions::default()
+    };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"dependencies":{"async":"1","JSONStream":"1","jsonstream":"1","through":"1"}}"#
+    );
+}
+
+#[test]
+fn test_dependency_order() {
+    let input = r#"{"devDependencies": {}, "scripts": {}, "dependencies": {}, "peerDependencies": {}, "jest": {}}"#;
+    let options = SortOptions {
+        pretty: false,
+        dependency_order: vec!["devDependencies".into(), "peerDependencies".into()],
+        ..SortOptions::default()
+    };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"scripts":{},"devDependencies":{},"peerDependencies":{},"dependencies":{},"jest":{}}"#
+    );
+}
+
+#[test]
+fn test_unicode_collation() {
+    let input = r#"{"keywords": ["zebra", "été", "Ångström", "apple", "ete"]}"#;
+    let options = SortOptions { pretty: false, unicode_collation: true, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options);
+    if cfg!(feature = "collation") {
+        assert_eq!(result.unwrap(), r#"{"keywords":["Ångström","apple","ete","été","zebra"]}"#);
+        let paths = serde_json::json!(["zebra/x", "été/b", "ete/a"]);
+        let Value::Array(paths) = paths else { unreachable!() };
+        assert_eq!(
+            sort_package_json::transforms::sort_paths_naturally(paths, &options),
+            serde_json::json!(["ete/a", "été/b", "zebra/x"]).as_array().unwrap().clone()
+        );
+    } else {
+        assert!(
+            matches!(&result, Err(SortError::Config(message)) if message.contains("`collation` feature")),
+            "{result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_alphabetical_field_order() {
+    let input = r#"{"version": "1.0.0", "name": "test", "_id": "x", "dependencies": {"b": "1", "a": "1"}, "custom": 1}"#;
+    let options = SortOptions {
+        pretty: false,
+        field_order: FieldOrder::Alphabetical,
+        ..SortOptions::default()
+    };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        r#"{"custom":1,"dependencies":{"a":"1","b":"1"},"name":"test","version":"1.0.0","_id":"x"}"#
+    );
+}
+
+#[test]
+fn test_normalize() {
+    let input = r#"{
+  "name": "test",
+  "bugs": "https://github.com/user/repo/issues",
+  "author": "Barney Rubble <b@rubble.com> (http://barnyrubble.tumblr.com/)",
+  "contributors": ["Fred <fred@example.com>", {"name": "Wilma"}],
+  "repository": "user/repo"
+}"#;
+    let options = SortOptions { normalize: true, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    insta::assert_snapshot!(result);
+
+    // Shorthands are left alone by default.
+    let result = sort_package_json_with_options(input, &SortOptions::default()).unwrap();
+    let parsed: Value = serde_json::from_str(&result).unwrap();
+    assert_eq!(parsed["repository"], "user/repo");
+}
+
+#[test]
+fn test_remove_empty_fields() {
+    let input = r#"{"name":"a","devDependencies":{},"files":[],"keywords":["x"],"workspaces":[]}"#;
+    let options = SortOptions {
+        pretty: false,
+        remove_empty_fields: true,
+        keep_empty_fields: vec!["workspaces".to_string()],
+        ..SortOptions::default()
+    };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(result, r#"{"name":"a","keywords":["x"],"workspaces":[]}"#);
+
+    // Empty fields are kept by default.
+    let options = SortOptions { pretty: false, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert!(result.contains(r#""devDependencies":{}"#));
+}
+
+#[test]
+fn test_reject_duplicate_keys() {
+    let input = "{\n  \"name\": \"a\",\n  \"dependencies\": {\"a\": \"1\", \"b\": \"2\"},\n  \"dependencies\": {}\n}";
+    let options = SortOptions { reject_duplicate_keys: true, ..SortOptions::default() };
+    let err = sort_package_json_with_options(input, &options).unwrap_err();
+    assert!(
+        matches!(&err, SortError::DuplicateKey { key, line: 4, column: 3 } if key == "dependencies"),
+        "{err:?}"
+    );
+
+    // Keys in different objects, escaped keys, and string contents are not duplicates.
+    let input = r#"{"a": {"x": 1}, "b": {"x": "\"x\": 1"}, "ca": [{"x": 1}, {"x": 2}]}"#;
+    assert!(sort_package_json_with_options(input, &options).is_ok());
+    let err = sort_package_json_with_options(r#"{"ca": 1, "\u0063a": 2}"#, &options).unwrap_err();
+    assert!(matches!(&err, SortError::DuplicateKey { key, .. } if key == "ca"), "{err:?}");
+
+    // Without the option the last value wins, as in serde_json.
+    assert!(sort_package_json_with_options(r#"{"a": 1, "a": 2}"#, &SortOptions::default()).is_ok());
+}
+
+#[test]
+fn test_diagnostics() {
+    let input = r#"{
+  "name": "test",
+  "private": "true",
+  "keywords": ["a", 1],
+  "type": "esm",
+  "name": "test"
+}"#;
+    let report = sort_package_json_with_report(input, &SortOptions::default()).unwrap();
+    assert_eq!(
+        report.diagnostics,
+        [
+            Diagnostic::DuplicateKey { key: "name".into(), line: 6, column: 3 },
+            Diagnostic::SuspiciousValue {
+                field: "private".into(),
+                message: "should be a boolean, found string".into()
+            },
+            Diagnostic::NonStringArrayEntry { field: "keywords".into(), value: Value::from(1) },
+            Diagnostic::SuspiciousValue {
+                field: "type".into(),
+                message: "should be \"module\" or \"commonjs\", found \"esm\"".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_lenient() {
+    let input = "{\n  // the package\n  \"version\": \"1.0.0\",\n  \"name\": \"a\",\n  \"files\": [\"b\", \"a\",],\n}\n";
+    assert!(matches!(
+        sort_package_json_with_options(input, &SortOptions::default()),
+        Err(SortError::Parse { .. })
+    ));
+
+    let options = SortOptions { lenient: true, ..SortOptions::default() };
+    assert_eq!(
+        sort_package_json_with_options(input, &options).unwrap(),
+        "{\n  \"name\": \"a\",\n  \"version\": \"1.0.0\",\n  \"files\": [\n    \"b\",\n    \"a\"\n  ]\n}\n"
+    );
+    let report = sort_package_json_with_report(input, &options).unwrap();
+    assert_eq!(
+        report.diagnostics,
+        [
+            Diagnostic::Recovered { line: 2, column: 3, message: "removed comment".into() },
+            Diagnostic::Recovered { line: 5, column: 21, message: "removed trailing comma".into() },
+            Diagnostic::Recovered { line: 5, column: 23, message: "removed trailing comma".into() },
+        ]
+    );
+
+    // Other syntax errors still fail.
+    assert!(sort_package_json_with_options("{\"a\": }", &options).is_err());
+}
+
+#[cfg(feature = "validation")]
+#[test]
+fn test_validation() {
+    use sort_package_json::{ValidationError, sort_package_json_with_validation};
+
+    let input = r#"{
+  "version": "1.0",
+  "name": "My-Package",
+  "author": {"email": "a@b.c"},
+  "keywords": ["a", 1],
+  "scripts": {"test": ["jest"]},
+  "type": "esm",
+  "private": true
+}"#;
+    let options = SortOptions::default();
+    let validated = sort_package_json_with_validation(input, &options).unwrap();
+    assert_eq!(validated.output, sort_package_json_with_options(input, &options).unwrap());
+    let errors: Vec<String> = validated.errors.iter().map(ValidationError::to_string).collect();
+    assert_eq!(
+        errors,
+        [
+            "/keywords/1: must be a string",
+            "/author: must have a `name`",
+            "/type: must be \"module\" or \"commonjs\"",
+            "/scripts/test: must be a string",
+            "/name: must be lowercase and URL-safe, not starting with `.` or `_`, found \"My-Package\"",
+            "/version: must be a valid semver version, found \"1.0\"",
+        ]
+    );
+
+    let valid = r#"{"name": "@scope/pkg", "version": "1.0.0-beta.1+build", "bin": "cli.js"}"#;
+    assert!(sort_package_json_with_validation(valid, &options).unwrap().errors.is_empty());
+}
+
+#[cfg(feature = "json5")]
+#[test]
+fn test_json5() {
+    use sort_package_json::sort_package_json5;
+
+    let input = r#"// Built by the release script.
+{
+  version: '1.0.0', // bumped on release
+  /* The published
+   * name. */
+  name: 'pkg',
+  dependencies: {
+    // Pinned until the next major.
+    b: "^2",
+    a: 0x1,
+  },
+  keywords: [
+    "b",
+    "a", // first
+    "b",
+  ],
+  "files": [
+    "lib", // compiled
+    "dist",
+    // generated
+  ],
+}
+"#;
+    let options = SortOptions::default();
+    let outcome = sort_package_json5(input, &options).unwrap();
+    assert_eq!(
+        outcome.output,
+        r#"// Built by the release script.
+{
+  /* The published
+   * name. */
+  name: 'pkg',
+  version: '1.0.0', // bumped on release
+  keywords: [
+    "a", // first
+    "b"
+  ],
+  files: [
+    "lib", // compiled
+    "dist"
+    // generated
+  ],
+  dependencies: {
+    a: 0x1,
+    // Pinned until the next major.
+    b: "^2"
+  }
+}
+"#
+    );
+    assert!(outcome.changed);
+    let again = sort_package_json5(&outcome.output, &options).unwrap();
+    assert_eq!(again.output, outcome.output);
+    assert!(!again.changed, "Sorted output should be reported as unchanged");
+
+    // JSONC keys stay quoted.
+    let input = "{\r\n  \"version\": \"1.0.0\",\r\n  \"name\": \"a\", // the name\r\n}\r\n";
+    let crlf = SortOptions { line_ending: LineEnding::CrLf, ..SortOptions::default() };
+    assert_eq!(
+        sort_package_json5(input, &crlf).unwrap().output,
+        "{\r\n  \"name\": \"a\", // the name\r\n  \"version\": \"1.0.0\"\r\n}\r\n"
+    );
+
+    // Values rewritten by a transform are written as JSON, keeping the field's comments.
+    let input = "{\n  repository: 'user/repo', // shorthand\n  \"name\": \"a\\u0062\",\n}";
+    let normalize = SortOptions { normalize: true, ..SortOptions::default() };
+    let output = sort_package_json5(input, &normalize).unwrap().output;
+    assert_eq!(
+        output,
+        r#"{
+  name: "a\u0062",
+  repository: {
+    "type": "git",
+    "url": "git+https://github.com/user/repo.git"
+  } // shorthand
+}
+"#
+    );
+
+    let err = sort_package_json5("{\n  name: 'a',\n  version: NaN,\n}", &options).unwrap_err();
+    assert!(matches!(err, SortError::Parse { line: 3, column: 12, .. }), "{err:?}");
+    let err = sort_package_json5("{\n  name: 'a'\n  version: 1,\n}", &options).unwrap_err();
+    assert!(matches!(err, SortError::Parse { line: 3, .. }), "{err:?}");
+    let err = sort_package_json5("['a'] // not a manifest", &options).unwrap_err();
+    assert!(matches!(err, SortError::NotAnObject), "{err:?}");
+}
+
+#[test]
+fn test_non_string_array_entries() {
+    let input = r#"{"keywords": [1, "b", null, "a", "b"], "files": ["dist", {"x": 1}, "dist"]}"#;
+    let options = SortOptions { pretty: false, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(result, r#"{"keywords":["a","b",1,null],"files":["dist",{"x":1}]}"#);
+}
+
+#[test]
+fn test_top_level_only() {
+    let input = r#"{"dependencies":{"b":"1","a":"1"},"keywords":["b","a","b"],"author":{"url":"u","name":"n"},"name":"x"}"#;
+    let options = SortOptions { pretty: false, top_level_only: true, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(
+        result,
+        r#"{"name":"x","keywords":["b","a","b"],"author":{"url":"u","name":"n"},"dependencies":{"b":"1","a":"1"}}"#
+    );
+}
+
+#[test]
+fn test_line_width() {
+    let input = r#"{
+  "name": "test",
+  "keywords": ["a-very-long-keyword-number-one", "a-very-long-keyword-number-two", "three"],
+  "files": ["dist"],
+  "engines": {"node": ">=18"},
+  "exports": {".": {"import": "./index.mjs", "require": "./index.cjs"}},
+  "dependencies": {},
+  "nested": {"a": [1, 2], "b": {"c": {}}}
+}"#;
+    let options = SortOptions { line_width: Some(80), ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    insta::assert_snapshot!(result);
+
+    // Without a width, output matches serde_json's pretty printer.
+    let result = sort_package_json_with_options(input, &SortOptions::default()).unwrap();
+    assert!(result.contains("\"files\": [\n    \"dist\"\n  ]"));
+}
+
+#[test]
+fn test_indent() {
+    let input = r#"{"version": "1.0.0", "name": "test", "files": ["dist"], "engines": {}}"#;
+
+    let options = SortOptions { indent: Indent::Tab, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(
+        result,
+        "{\n\t\"name\": \"test\",\n\t\"version\": \"1.0.0\",\n\t\"files\": [\n\t\t\"dist\"\n\t],\n\t\"engines\": {}\n}\n"
+    );
+
+    let options = SortOptions { indent: Indent::Spaces(4), ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert!(result.starts_with("{\n    \"name\": \"test\",\n"), "{result}");
+
+    // The custom printer matches serde_json's layout for the default indent.
+    let options = SortOptions { indent: Indent::Spaces(2), ..SortOptions::default() };
+    let fixture = include_str!("fixtures/package.json");
+    let expected = sort_package_json_with_options(fixture, &options).unwrap();
+    let options = SortOptions { line_width: Some(0), ..options };
+    assert_eq!(sort_package_json_with_options(fixture, &options).unwrap(), expected);
+}
+
+#[test]
+fn test_line_ending() {
+    let input = "{\r\n  \"version\": \"1.0.0\",\r\n  \"name\": \"a\\nb\"\r\n}\r\n";
+    let options = SortOptions { line_ending: LineEnding::CrLf, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(result, "{\r\n  \"name\": \"a\\nb\",\r\n  \"version\": \"1.0.0\"\r\n}\r\n");
+    assert!(!sort_package_json_with_outcome(&result, &options).unwrap().changed);
+}
+
+#[test]
+fn test_trailing_newline() {
+    let sort_with = |input: &str, pretty: bool, trailing_newline: TrailingNewline| {
+        let options = SortOptions { pretty, trailing_newline, ..SortOptions::default() };
+        sort_package_json_with_options(input, &options).unwrap()
+    };
+    let input = r#"{"name": "a"}"#;
+
+    assert!(sort_with(input, true, TrailingNewline::PrettyOnly).ends_with("}\n"));
+    assert!(sort_with(input, false, TrailingNewline::PrettyOnly).ends_with('}'));
+    assert!(sort_with(input, false, TrailingNewline::Always).ends_with("}\n"));
+    assert!(sort_with(input, true, TrailingNewline::Never).ends_with('}'));
+    assert!(sort_with(input, true, TrailingNewline::Preserve).ends_with('}'));
+    assert!(sort_with(&format!("{input}\n"), false, TrailingNewline::Preserve).ends_with("}\n"));
+}
+
+#[test]
+fn test_sorted_package_json() {
+    let input = "{\"version\": \"1.0.0\", \"name\": \"test\"}\n";
+    let options =
+        SortOptions { trailing_newline: TrailingNewline::Preserve, ..SortOptions::default() };
+    let mut pkg = SortedPackageJson::parse(input, &options).unwrap();
+    assert!(pkg.as_map().keys().eq(["name", "version"]));
+    assert_eq!(pkg.get("name"), Some(&Value::from("test")));
+
+    *pkg.get_mut("version").unwrap() = Value::from("2.0.0");
+    assert_eq!(
+        pkg.to_string(&options).unwrap(),
+        "{\n  \"name\": \"test\",\n  \"version\": \"2.0.0\"\n}\n"
+    );
+    let compact = SortOptions { pretty: false, ..options };
+    assert_eq!(pkg.to_string(&compact).unwrap(), "{\"name\":\"test\",\"version\":\"2.0.0\"}\n");
+}
+
+#[test]
+fn test_sort_from_reader() {
+    let input = r#"{"version": "1.0.0", "name": "test"}"#;
+    let options = SortOptions::default();
+    let mut output = Vec::new();
+    sort_from_reader(input.as_bytes(), &mut output, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        sort_package_json_with_options(input, &options).unwrap()
+    );
+
+    let err = sort_from_reader(&b"{\"name\": \"\xff\"}"[..], Vec::new(), &options).unwrap_err();
+    assert!(matches!(err, SortError::Io(_)), "{err:?}");
+}
+
+#[test]
+fn test_sort_into() {
+    let options = SortOptions { line_ending: LineEnding::CrLf, ..SortOptions::default() };
+    let mut out = String::from("stale");
+    for input in [r#"{"version": "1.0.0", "name": "a"}"#, "\u{feff}{\"b\": 1, \"a\": [2]}"] {
+        sort_package_json_into(input, &mut out, &options).unwrap();
+        assert_eq!(out, sort_package_json_with_options(input, &options).unwrap());
+    }
+
+    assert!(sort_package_json_into("[]", &mut out, &options).is_err());
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_sort_many() {
+    let inputs: Vec<String> =
+        (0..20).map(|i| format!(r#"{{"version": "{i}.0.0", "name": "p{i}"}}"#)).collect();
+    let mut inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+    inputs.insert(7, "[]");
+    let options = SortOptions::default();
+    let results = sort_many(&inputs, &options);
+    assert_eq!(results.len(), inputs.len());
+    for (input, result) in inputs.iter().zip(&results) {
+        match sort_package_json_with_options(input, &options) {
+            Ok(expected) => assert_eq!(result.as_ref().unwrap(), &expected),
+            Err(_) => assert!(matches!(result, Err(SortError::NotAnObject))),
+        }
+    }
+    assert!(sort_many(&[], &options).is_empty());
+}
+
+#[test]
+fn test_custom_field_order() {
+    let input = r#"{"_id": "x", "zzz": 1, "name": "a", "scripts": {}, "version": "1.0.0", "keywords": ["b", "a"]}"#;
+    let options = SortOptions {
+        pretty: false,
+        field_order: FieldOrder::Custom(vec!["version".into(), "zzz".into(), "name".into()]),
+        ..SortOptions::default()
+    };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(
+        result,
+        r#"{"version":"1.0.0","zzz":1,"name":"a","keywords":["a","b"],"scripts":{},"_id":"x"}"#
+    );
+}
+
+#[test]
+fn test_config_file() {
+    let options =
+        SortOptions::from_config("tests/fixtures/config/.sortpackagejsonrc.json").unwrap();
+    assert_eq!(options.field_order, FieldOrder::Custom(vec!["version".into(), "name".into()]));
+    assert_eq!(options.field_strategies.get("dependencies"), Some(&FieldStrategy::Preserve));
+    assert_eq!(options.indent, Indent::Tab);
+    assert!(options.group_sections);
+    assert_eq!(options.line_ending, LineEnding::CrLf);
+    assert_eq!(options.private_fields, PrivateFieldPlacement::First);
+    assert!(options.sort_dependencies, "Unset keys keep their defaults");
+
+    #[cfg(feature = "toml")]
+    {
+        let toml =
+            SortOptions::from_config("tests/fixtures/config/.sortpackagejsonrc.toml").unwrap();
+        assert_eq!(format!("{toml:?}"), format!("{options:?}"));
+    }
+
+    let err = SortOptions::from_config("tests/fixtures/config/missing.json").unwrap_err();
+    assert!(matches!(err, SortError::Io(_)), "{err:?}");
+    let err = SortOptions::from_config("tests/fixtures/config/unknown-key.json").unwrap_err();
+    assert!(
+        matches!(&err, SortError::Config(message) if message.contains("unknown key")),
+        "{err:?}"
+    );
+    let err = SortOptions::from_config("tests/fixtures/package.json").unwrap_err();
+    assert!(
+        matches!(&err, SortError::Config(message) if message.contains("no `sortPackageJson`")),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn test_config_discovery() {
+    let root = std::path::Path::new("tests/fixtures/config/discover");
+    let found = find_config_file(root.join("packages/a")).unwrap();
+    assert_eq!(found, root.join(".sortpackagejsonrc"));
+    let config = Config::from_file(found).unwrap();
+    assert_eq!(config.options.indent, Indent::Spaces(4));
+    assert_eq!(config.ignore, ["**/fixtures/**"]);
+
+    assert_eq!(find_config_file("tests/fixtures"), None);
+
+    // A `sortPackageJson` field in package.json is a config too.
+    let root = std::path::Path::new("tests/fixtures/config/manifest");
+    let found = find_config_file(root).unwrap();
+    assert_eq!(found, root.join("package.json"));
+    let options = Config::from_file(found).unwrap().options;
+    assert_eq!(options.indent, Indent::Spaces(4));
+    assert_eq!(options.field_strategies.get("scripts"), Some(&FieldStrategy::Preserve));
+}
+
+#[test]
+fn test_config_extends() {
+    let options =
+        SortOptions::from_config("tests/fixtures/config/extends/nested/.sortpackagejsonrc.json")
+            .unwrap();
+    // The package overrides the relative base, and the config's own keys override both.
+    assert_eq!(options.indent, Indent::Tab);
+    assert!(options.natural_sort);
+    assert!(options.sort_scripts);
+    assert_eq!(options.field_order, FieldOrder::Alphabetical);
+
+    let err = SortOptions::from_config("tests/fixtures/config/extends/cycle.json").unwrap_err();
+    assert!(matches!(&err, SortError::Config(message) if message.contains("circular")), "{err:?}");
+}
+
+#[test]
+fn test_visitor() {
+    #[derive(Default)]
+    struct PinAndCollect {
+        fields: Vec<String>,
+        people: Vec<String>,
+        exports: usize,
+    }
+
+    impl Visitor for PinAndCollect {
+        fn visit_field(&mut self, key: &str, _value: &mut Value) {
+            self.fields.push(key.to_string());
+        }
+
+        fn visit_dependencies(
+            &mut self,
+            _field: &str,
+            dependencies: &mut serde_json::Map<String, Value>,
+        ) {
+            for version in dependencies.values_mut() {
+                if let Some(pinned) = version.as_str().and_then(|v| v.strip_prefix('^')) {
+                    *version = Value::from(pinned);
+                }
+            }
+        }
+
+        fn visit_person(&mut self, field: &str, person: &mut Value) {
+            let name = person.get("name").and_then(Value::as_str).unwrap_or_default();
+            self.people.push(format!("{field}:{name}"));
+        }
+
+        fn visit_exports(&mut self, _field: &str, _exports: &mut Value) {
+            self.exports += 1;
+        }
+    }
+
+    let input = r#"{
+  "dependencies": {"b": "^2.0.0", "a": "1.0.0"},
+  "contributors": [{"name": "c"}, {"name": "d"}],
+  "exports": "./index.js",
+  "author": {"name": "a"},
+  "name": "test"
+}"#;
+    let options = SortOptions { pretty: false, ..SortOptions::default() };
+    let mut visitor = PinAndCollect::default();
+    let result = sort_package_json_with_visitor(input, &options, &mut visitor).unwrap();
+    assert!(result.contains(r#""dependencies":{"a":"1.0.0","b":"2.0.0"}"#), "{result}");
+    assert_eq!(visitor.fields, ["name", "author", "contributors", "exports", "dependencies"]);
+    assert_eq!(visitor.people, ["author:a", "contributors:c", "contributors:d"]);
+    assert_eq!(visitor.exports, 1);
+}
+
+#[test]
+fn test_text_edits() {
+    let options = SortOptions::default();
+    let input = "{\n  \"version\": \"1.0.0\",\n  \"name\": \"test\",\n  \"description\": \"d\",\n  \"private\": true\n}";
+    let edits = sort_package_json_edits(input, &options).unwrap();
+    assert!(edits.windows(2).all(|pair| pair[0].range.end <= pair[1].range.start));
+    assert!(edits.iter().all(|edit| edit.range.len() < input.len()), "{edits:?}");
+
+    let mut patched = input.to_string();
+    for edit in edits.iter().rev() {
+        patched.replace_range(edit.range.clone(), &edit.new_text);
+    }
+    assert_eq!(patched, sort_package_json_with_options(input, &options).unwrap());
+
+    let sorted = sort_package_json_with_options(input, &options).unwrap();
+    assert_eq!(sort_package_json_edits(&sorted, &options).unwrap(), []);
+
+    // Reversing every line is the worst case for the diff.
+    let deps: Vec<String> =
+        (0..2000).rev().map(|i| format!("    \"dep-{i:04}\": \"1.0.0\"")).collect();
+    let input = format!("{{\n  \"dependencies\": {{\n{}\n  }}\n}}\n", deps.join(",\n"));
+    let mut patched = input.clone();
+    for edit in sort_package_json_edits(&input, &options).unwrap().iter().rev() {
+        patched.replace_range(edit.range.clone(), &edit.new_text);
+    }
+    assert_eq!(patched, sort_package_json_with_options(&input, &options).unwrap());
+}
+
+#[test]
+fn test_section_helpers() {
+    let map =
+        |json: &str| -> serde_json::Map<String, Value> { serde_json::from_str(json).unwrap() };
+    let keys = |obj: &serde_json::Map<String, Value>| obj.keys().cloned().collect::<Vec<_>>();
+
+    let options = SortOptions { case_insensitive_dependencies: true, ..SortOptions::default() };
+    let deps = sort_dependencies(map(r#"{"react": "1", "JSONStream": "1", "a": "1"}"#), &options);
+    assert_eq!(keys(&deps), ["a", "JSONStream", "react"]);
+
+    let scripts = sort_scripts(map(r#"{"test": "", "build": ""}"#), &SortOptions::default());
+    assert_eq!(keys(&scripts), ["build", "test"]);
+
+    let exports = map(r#"{"./utils": "./u.js", ".": "./i.js"}"#);
+    assert_eq!(keys(&sort_exports(exports.clone(), &SortOptions::default())), ["./utils", "."]);
+    let options = SortOptions { exports_mode: ExportsMode::GroupPaths, ..SortOptions::default() };
+    assert_eq!(keys(&sort_exports(exports, &options)), [".", "./utils"]);
+}
+
+#[test]
+fn test_transforms() {
+    use sort_package_json::transforms;
+
+    let map =
+        |json: &str| -> serde_json::Map<String, Value> { serde_json::from_str(json).unwrap() };
+    let options = SortOptions::default();
+
+    let obj =
+        transforms::sort_object_alphabetically(map(r#"{"b": {"d": 1, "c": 2}, "a": 1}"#), &options);
+    assert_eq!(Value::from(obj).to_string(), r#"{"a":1,"b":{"d":1,"c":2}}"#);
+    let obj =
+        transforms::sort_object_recursive(map(r#"{"b": {"d": 1, "c": 2}, "a": 1}"#), &options);
+    assert_eq!(Value::from(obj).to_string(), r#"{"a":1,"b":{"c":2,"d":1}}"#);
+
+    let person =
+        transforms::sort_people_object(map(r#"{"url": "u", "x": 1, "name": "n"}"#), &options);
+    assert_eq!(person.keys().collect::<Vec<_>>(), ["name", "url", "x"]);
+
+    let paths = serde_json::json!(["dist/10", "dist/2", 1, "dist/2"]);
+    let Value::Array(paths) = paths else { unreachable!() };
+    assert_eq!(
+        transforms::sort_paths_naturally(paths, &options),
+        serde_json::json!(["dist/2", "dist/10", 1]).as_array().unwrap().clone()
+    );
+    let paths = serde_json::json!(["pkg-a", "pkg/**/x", "pkg", "pkg/b"]);
+    let Value::Array(paths) = paths else { unreachable!() };
+    assert_eq!(
+        transforms::sort_paths_naturally(paths, &options),
+        serde_json::json!(["pkg", "pkg/**/x", "pkg/b", "pkg-a"]).as_array().unwrap().clone()
+    );
+}
+
+#[test]
+fn test_group_sections() {
+    let input = r#"{
+  "_id": "x",
+  "custom": true,
+  "devDependencies": {"b": "1"},
+  "dependencies": {"a": "1"},
+  "scripts": {"test": "jest"},
+  "main": "index.js",
+  "license": "MIT",
+  "version": "1.0.0",
+  "name": "test"
+}"#;
+    let options = SortOptions { group_sections: true, ..SortOptions::default() };
+    let result = sort_package_json_with_options(input, &options).unwrap();
+    insta::assert_snapshot!(result);
+}